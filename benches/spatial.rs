@@ -0,0 +1,68 @@
+use indifi::*;
+use notan::math::Vec2;
+use notan::random::rand::random;
+use notan::random::utils::Random;
+use std::hint::black_box;
+use std::time::Instant;
+
+const ENTITY_COUNTS: [usize; 3] = [1_000, 5_000, 20_000];
+const ITERATIONS: u32 = 20;
+
+fn populate(count: usize) -> World {
+    let mut world = World::new();
+    let size = (AREA_SIZE * Chunk::size()) as f32;
+    for _ in 0..count {
+        let (x, y) = (random::<f32>() * size, random::<f32>() * size);
+        NpcBuilder::new(Behaviour::wander(Vec2::new(x, y)))
+            .position(x, y)
+            .spawn(&mut world);
+    }
+    PlayerBuilder::new().spawn(&mut world);
+    spatial_system(&mut world);
+    world
+}
+
+fn bench(name: &str, count: usize, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    println!(
+        "{:<16} {:>6} entities: {:>9.3}ms",
+        name,
+        count,
+        (start.elapsed().as_secs_f64() * 1000.0) / (ITERATIONS as f64)
+    );
+}
+
+fn main() {
+    for count in ENTITY_COUNTS {
+        let mut world = populate(count);
+
+        bench("spatial update", count, || {
+            for (_, transform) in world.transforms.iter_mut() {
+                transform
+                    .mut_position()
+                    .translate(random::<f32>() * 8.0 - 4.0, random::<f32>() * 8.0 - 4.0);
+            }
+            spatial_system(&mut world);
+        });
+
+        bench("radius query", count, || {
+            for (_, transform) in world.transforms.iter() {
+                black_box(world.spatial.query_radius(transform.position().vec(), SENSE_RADIUS));
+            }
+        });
+
+        bench("rect query", count, || {
+            let center = Vec2::new(2048.0, 2048.0);
+            let half_screen = Vec2::new(512.0, 288.0);
+            black_box(world.spatial.query_rect(center - half_screen, center + half_screen));
+        });
+
+        bench("collision", count, || collision_system(&mut world));
+
+        let mut rng = Random::new(0);
+        bench("ai sensing", count, || ai_system(&mut world, 1.0 / 60.0, &mut rng));
+    }
+}
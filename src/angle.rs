@@ -0,0 +1,70 @@
+use crate::*;
+
+//a direction in degrees, always kept in 0..360 so comparing and storing angles never has to
+//care which turn they are on, 0 points along +x and 90 along +y (down on screen)
+#[derive(Copy, Clone, PartialEq, PartialOrd, Default, Debug)]
+pub struct Angle(f32);
+
+impl Angle {
+    pub const ZERO: Angle = Angle(0.0);
+
+    pub fn from_degrees(degrees: f32) -> Angle {
+        if !degrees.is_finite() {
+            return Angle::ZERO;
+        }
+        let wrapped = degrees.rem_euclid(360.0);
+        //rem_euclid rounds tiny negative values up to exactly 360
+        Angle(if wrapped >= 360.0 { 0.0 } else { wrapped })
+    }
+
+    pub fn from_radians(radians: f32) -> Angle {
+        Angle::from_degrees(radians.to_degrees())
+    }
+
+    //direction from `from` towards `to`
+    pub fn between(from: Vec2, to: Vec2) -> Angle {
+        let delta = to - from;
+        Angle::from_radians(delta.y.atan2(delta.x))
+    }
+
+    pub fn degrees(self) -> f32 {
+        self.0
+    }
+
+    pub fn radians(self) -> f32 {
+        self.0.to_radians()
+    }
+
+    pub fn vec(self) -> Vec2 {
+        Vec2::from_angle(self.radians())
+    }
+
+    //shortest signed turn from this angle to `to`, in -180..180
+    pub fn difference(self, to: Angle) -> f32 {
+        let difference = (to.0 - self.0).rem_euclid(360.0);
+        if difference >= 180.0 { difference - 360.0 } else { difference }
+    }
+
+    //blends along the shorter arc, `t` of 0 is this angle and 1 is `to`
+    pub fn lerp(self, to: Angle, t: f32) -> Angle {
+        self + self.difference(to) * t
+    }
+
+    //turns at most `max_degrees` towards `to` without passing it
+    pub fn rotate_towards(self, to: Angle, max_degrees: f32) -> Angle {
+        let difference = self.difference(to);
+        if difference.abs() <= max_degrees {
+            return to;
+        }
+        self + max_degrees.copysign(difference)
+    }
+}
+
+//turns by a number of degrees, wrapping around
+impl std::ops::Add<f32> for Angle {
+    type Output = Angle;
+
+    fn add(self, degrees: f32) -> Angle {
+        Angle::from_degrees(self.0 + degrees)
+    }
+}
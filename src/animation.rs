@@ -0,0 +1,128 @@
+use crate::*;
+
+//below this many units per second an entity counts as standing
+const WALK_THRESHOLD: f32 = 2.0;
+
+//which directions an animated sprite can face
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Facing {
+    //right, down, left and up
+    Four,
+    //steps of 45 degrees
+    #[default]
+    Eight,
+    //the exact rotation, the closest row of the sheet is turned the rest of the way
+    Free,
+}
+
+impl Facing {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Facing::Four => "four",
+            Facing::Eight => "eight",
+            Facing::Free => "free",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Facing> {
+        match name {
+            "four" => Some(Facing::Four),
+            "eight" => Some(Facing::Eight),
+            "free" => Some(Facing::Free),
+            _ => None,
+        }
+    }
+
+    //the rotation rounded to the closest direction that can be shown
+    pub fn snap(&self, rotation: Angle) -> Angle {
+        let step = match self {
+            Facing::Four => 90.0,
+            Facing::Eight => 45.0,
+            Facing::Free => {
+                return rotation;
+            }
+        };
+        Angle::from_degrees((rotation.degrees() / step).round() * step)
+    }
+}
+
+//layout of a sheet, every row is one direction with the idle frames first and the walk frames
+//after them, rows start facing right and go clockwise
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SpriteSheet {
+    pub frame_size: (f32, f32),
+    pub directions: usize,
+    pub idle_frames: usize,
+    pub walk_frames: usize,
+    //frames per second, the walk frames at full speed
+    pub idle_frame_rate: f32,
+    pub walk_frame_rate: f32,
+    pub facing: Facing,
+}
+
+//layout of assets/player_sheet.png
+pub const PLAYER_SHEET: SpriteSheet = SpriteSheet {
+    frame_size: (16.0, 16.0),
+    directions: 8,
+    idle_frames: 2,
+    walk_frames: 4,
+    idle_frame_rate: 2.0,
+    walk_frame_rate: 10.0,
+    facing: Facing::Eight,
+};
+
+impl SpriteSheet {
+    //the row closest to the facing and how many degrees it still has to be turned
+    pub fn row(&self, rotation: Angle) -> (usize, f32) {
+        let facing = self.facing.snap(rotation);
+        let directions = self.directions.max(1);
+        let step = 360.0 / (directions as f32);
+        let row = ((facing.degrees() / step).round() as usize) % directions;
+        (row, Angle::from_degrees((row as f32) * step).difference(facing))
+    }
+
+    pub fn column(&self, animation: &Animation) -> usize {
+        let (first, frames, frame_rate) = if animation.is_walking() && self.walk_frames > 0 {
+            (self.idle_frames, self.walk_frames, self.walk_frame_rate)
+        } else {
+            (0, self.idle_frames.max(1), self.idle_frame_rate)
+        };
+        first + ((animation.time() * frame_rate) as usize) % frames
+    }
+
+    //position and size of the frame to draw inside the texture and how far to rotate it
+    pub fn frame(&self, animation: &Animation, rotation: Angle) -> ((f32, f32), (f32, f32), f32) {
+        let (row, turn) = self.row(rotation);
+        let (w, h) = self.frame_size;
+        (((self.column(animation) as f32) * w, (row as f32) * h), (w, h), turn)
+    }
+}
+
+//where an entity is in its animation, advanced every frame by animation_system
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Animation {
+    time: f32,
+    walking: bool,
+}
+
+impl Animation {
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn is_walking(&self) -> bool {
+        self.walking
+    }
+
+    //`speed` is how fast the entity moves and `full_speed` its top speed, walking at full
+    //speed plays the walk frames at the sheet's walk_frame_rate
+    pub fn advance(&mut self, delta: f32, speed: f32, full_speed: f32) {
+        let walking = speed > WALK_THRESHOLD;
+        if walking != self.walking {
+            self.walking = walking;
+            self.time = 0.0;
+        }
+        let pace = if walking && full_speed > 0.0 { speed / full_speed } else { 1.0 };
+        self.time += delta * pace;
+    }
+}
@@ -0,0 +1,215 @@
+use crate::*;
+use std::io;
+use std::path::{ Path, PathBuf };
+use std::time::SystemTime;
+
+//the art in the source tree, debug builds read it from here so changes show up without a rebuild
+pub const SOURCE_ASSETS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/assets");
+//seconds between checks of the watched files
+pub const WATCH_INTERVAL: f32 = 0.5;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AssetFile {
+    Atlas,
+    Font,
+    PlayerSheet,
+}
+
+impl AssetFile {
+    pub const ALL: [AssetFile; 3] = [AssetFile::Atlas, AssetFile::Font, AssetFile::PlayerSheet];
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            AssetFile::Atlas => "atlas_test_bigger.png",
+            AssetFile::Font => "Ubuntu-B.ttf",
+            AssetFile::PlayerSheet => "player_sheet.png",
+        }
+    }
+
+    //the asset a path loaded by notan's asset manager points at
+    pub fn from_path(path: &str) -> Option<AssetFile> {
+        AssetFile::ALL.into_iter().find(|asset| path.ends_with(asset.file_name()))
+    }
+
+    //the copy built into the binary, used when there is no assets directory or the file is broken
+    pub fn embedded(&self) -> &'static [u8] {
+        match self {
+            AssetFile::Atlas => include_bytes!("assets/atlas_test_bigger.png"),
+            AssetFile::Font => include_bytes!("assets/Ubuntu-B.ttf"),
+            AssetFile::PlayerSheet => include_bytes!("assets/player_sheet.png"),
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+//a file checked for changes by its modification time, a file that appears counts as changed
+pub struct WatchedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl WatchedFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let modified = modified(&path);
+        WatchedFile { path, modified }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    //true once after every write, deleting the file is not a change
+    pub fn changed(&mut self) -> bool {
+        match modified(&self.path) {
+            Some(time) if Some(time) != self.modified => {
+                self.modified = Some(time);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+//reads the art from a directory and notices when it changes, or only uses the embedded copies
+pub struct AssetDir {
+    dir: Option<PathBuf>,
+    files: Vec<(AssetFile, WatchedFile)>,
+}
+
+impl AssetDir {
+    //nothing is read from disk or watched
+    pub fn embedded() -> Self {
+        AssetDir { dir: None, files: Vec::new() }
+    }
+
+    pub fn from_dir(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let files = AssetFile::ALL.iter()
+            .map(|asset| (*asset, WatchedFile::new(dir.join(asset.file_name()))))
+            .collect();
+        AssetDir { dir: Some(dir), files }
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    //the file in the assets directory, an error without one
+    pub fn read(&self, asset: AssetFile) -> io::Result<Vec<u8>> {
+        match &self.dir {
+            Some(dir) => std::fs::read(dir.join(asset.file_name())),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "there is no assets directory")),
+        }
+    }
+
+    //the file from disk if it is there, the embedded copy otherwise
+    pub fn bytes(&self, asset: AssetFile) -> Vec<u8> {
+        match self.read(asset) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                if self.dir.is_some() {
+                    eprintln!("could not read {}, using the built in one: {}", asset.file_name(), e);
+                }
+                asset.embedded().to_vec()
+            }
+        }
+    }
+
+    //a texture from the file, from the embedded copy if the file can not be used
+    pub fn texture(&self, gfx: &mut Graphics, asset: AssetFile) -> Texture {
+        gfx.create_texture()
+            .from_image(&self.bytes(asset))
+            .build()
+            .or_else(|e| {
+                eprintln!("could not load {}, using the built in one: {}", asset.file_name(), e);
+                gfx.create_texture().from_image(asset.embedded()).build()
+            })
+            .unwrap()
+    }
+
+    pub fn font(&self, gfx: &mut Graphics) -> Font {
+        gfx.create_font(&self.bytes(AssetFile::Font))
+            .or_else(|e| {
+                eprintln!("could not load {}, using the built in one: {}", AssetFile::Font.file_name(), e);
+                gfx.create_font(AssetFile::Font.embedded())
+            })
+            .unwrap()
+    }
+
+    //starts loading the files in the directory through notan, the missing ones are not in the
+    //list and come from the embedded copies when taken
+    pub fn load_list(&self, assets: &mut Assets) -> Result<AssetList, String> {
+        let paths: Vec<String> = AssetFile::ALL.iter()
+            .filter_map(|asset| self.path(*asset))
+            .filter(|path| path.is_file())
+            .map(|path| path.display().to_string())
+            .collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        assets.load_list(&paths)
+    }
+
+    fn path(&self, asset: AssetFile) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(asset.file_name()))
+    }
+
+    fn take<A: Clone + Send + Sync + 'static>(&self, list: &mut AssetList, asset: AssetFile) -> Option<A> {
+        let path = self.path(asset)?.display().to_string();
+        if !list.contains(&path) {
+            return None;
+        }
+        let taken = list.take::<A>(&path).ok()?;
+        let loaded = taken.lock().map(|loaded| loaded.clone());
+        loaded
+    }
+
+    //a texture from a finished list, the embedded one if it was not in it
+    pub fn take_texture(&self, gfx: &mut Graphics, list: &mut AssetList, asset: AssetFile) -> Texture {
+        self.take(list, asset).unwrap_or_else(|| AssetDir::embedded().texture(gfx, asset))
+    }
+
+    pub fn take_font(&self, gfx: &mut Graphics, list: &mut AssetList) -> Font {
+        self.take(list, AssetFile::Font).unwrap_or_else(|| AssetDir::embedded().font(gfx))
+    }
+
+    //the assets written since the last call
+    pub fn changed(&mut self) -> Vec<AssetFile> {
+        self.files
+            .iter_mut()
+            .filter_map(|(asset, file)| file.changed().then_some(*asset))
+            .collect()
+    }
+}
+
+//notan parsers for the art, a broken file is reported and replaced by the embedded copy
+//instead of stopping the game
+pub fn asset_loaders() -> [AssetLoader; 2] {
+    [
+        AssetLoader::new().use_parser(parse_texture).extensions(&["png"]),
+        AssetLoader::new().use_parser(parse_font).extensions(&["ttf"]),
+    ]
+}
+
+fn parse_texture(id: &str, data: Vec<u8>, gfx: &mut Graphics) -> Result<Texture, String> {
+    gfx.create_texture()
+        .from_image(&data)
+        .build()
+        .or_else(|e| {
+            let asset = AssetFile::from_path(id).ok_or(e.clone())?;
+            eprintln!("could not load {}, using the built in one: {}", id, e);
+            gfx.create_texture().from_image(asset.embedded()).build()
+        })
+}
+
+fn parse_font(id: &str, data: Vec<u8>, gfx: &mut Graphics) -> Result<Font, String> {
+    gfx.create_font(&data).or_else(|e| {
+        let asset = AssetFile::from_path(id).ok_or(e.clone())?;
+        eprintln!("could not load {}, using the built in one: {}", id, e);
+        gfx.create_font(asset.embedded())
+    })
+}
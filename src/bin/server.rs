@@ -0,0 +1,52 @@
+use indifi::net::DEFAULT_PORT;
+use indifi::server::{ Server, ServerEvent };
+use indifi::{ AREA_SIZE, MAX_AREA_SIZE };
+
+//`server [address] [seed] [area size]`, the seed is random and the world AREA_SIZE chunks wide
+//when left out
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| format!("0.0.0.0:{}", DEFAULT_PORT));
+    let seed = match args.next() {
+        Some(seed) =>
+            seed.parse().unwrap_or_else(|_| {
+                eprintln!("seed has to be a number, not {}", seed);
+                std::process::exit(1);
+            }),
+        None => notan::random::rand::random(),
+    };
+    let area_size = match args.next() {
+        Some(area_size) =>
+            area_size
+                .parse()
+                .ok()
+                .filter(|area_size| (1..=MAX_AREA_SIZE).contains(area_size))
+                .unwrap_or_else(|| {
+                    eprintln!("area size has to be a number between 1 and {}, not {}", MAX_AREA_SIZE, area_size);
+                    std::process::exit(1);
+                }),
+        None => AREA_SIZE,
+    };
+    let mut server = match Server::bind_sized(&addr, seed, area_size) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("could not bind {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    };
+    println!("listening on {} with seed {} and {}x{} chunks", addr, seed, area_size, area_size);
+    let result = server.run(|event| {
+        match event {
+            ServerEvent::Joined { id, display_name, addr } => {
+                println!("{} joined as player {} from {}", display_name, id, addr)
+            }
+            ServerEvent::Left { id } => println!("player {} left", id),
+            ServerEvent::SendFailed { addr, error } => eprintln!("could not send to {}: {}", addr, error),
+            ServerEvent::Refused { addr } => println!("refused {}, the server is full", addr),
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("server stopped: {}", e);
+        std::process::exit(1);
+    }
+}
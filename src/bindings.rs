@@ -0,0 +1,417 @@
+use crate::*;
+use notan::input::keyboard::Keyboard;
+use std::io;
+use std::path::Path;
+
+pub const DEFAULT_BINDINGS_PATH: &str = "bindings.cfg";
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    StrafeLeft,
+    StrafeRight,
+    ZoomIn,
+    ZoomOut,
+    ToggleFullscreen,
+    ToggleDebug,
+    ToggleOverlay,
+    OpenBindings,
+    Pause,
+    OpenEditor,
+    ToggleConsole,
+    Screenshot,
+    //writes the world in the editor
+    Save,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ToggleFullscreen,
+        Action::ToggleDebug,
+        Action::ToggleOverlay,
+        Action::OpenBindings,
+        Action::Pause,
+        Action::OpenEditor,
+        Action::ToggleConsole,
+        Action::Screenshot,
+        Action::Save,
+    ];
+
+    //the name used in the bindings file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBack => "move_back",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::ToggleFullscreen => "toggle_fullscreen",
+            Action::ToggleDebug => "toggle_debug",
+            Action::ToggleOverlay => "toggle_overlay",
+            Action::OpenBindings => "open_bindings",
+            Action::Pause => "pause",
+            Action::OpenEditor => "open_editor",
+            Action::ToggleConsole => "toggle_console",
+            Action::Screenshot => "screenshot",
+            Action::Save => "save",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        let keys: &[KeyCode] = match self {
+            Action::MoveForward => &[KeyCode::W, KeyCode::Up],
+            Action::MoveBack => &[KeyCode::S, KeyCode::Down],
+            Action::StrafeLeft => &[KeyCode::A, KeyCode::Left],
+            Action::StrafeRight => &[KeyCode::D, KeyCode::Right],
+            Action::ZoomIn => &[KeyCode::P],
+            Action::ZoomOut => &[KeyCode::O],
+            Action::ToggleFullscreen => &[KeyCode::F11],
+            Action::ToggleDebug => &[KeyCode::L],
+            Action::ToggleOverlay => &[KeyCode::F3],
+            Action::OpenBindings => &[KeyCode::F1],
+            Action::Pause => &[KeyCode::Escape],
+            Action::OpenEditor => &[KeyCode::F2],
+            Action::ToggleConsole => &[KeyCode::Grave],
+            Action::Screenshot => &[KeyCode::F12],
+            Action::Save => &[KeyCode::F5],
+        };
+        keys.iter().copied().map(Binding::new).collect()
+    }
+}
+
+//keys that can be written in the bindings file, by their KeyCode name
+const KEYS: &[KeyCode] = &[
+    KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+    KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9, KeyCode::Key0,
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Escape, KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5,
+    KeyCode::F6, KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Insert, KeyCode::Home, KeyCode::Delete, KeyCode::End, KeyCode::PageDown, KeyCode::PageUp,
+    KeyCode::Left, KeyCode::Up, KeyCode::Right, KeyCode::Down,
+    KeyCode::Back, KeyCode::Return, KeyCode::Space, KeyCode::Tab,
+    KeyCode::Numpad0, KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3, KeyCode::Numpad4,
+    KeyCode::Numpad5, KeyCode::Numpad6, KeyCode::Numpad7, KeyCode::Numpad8, KeyCode::Numpad9,
+    KeyCode::Minus, KeyCode::Equals, KeyCode::Comma, KeyCode::Period, KeyCode::Slash,
+    KeyCode::Semicolon, KeyCode::Apostrophe, KeyCode::LBracket, KeyCode::RBracket,
+    KeyCode::Backslash, KeyCode::Grave,
+    KeyCode::LShift, KeyCode::RShift, KeyCode::LControl, KeyCode::RControl, KeyCode::LAlt, KeyCode::RAlt,
+];
+
+fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEYS.iter()
+        .copied()
+        .find(|key| key_name(*key).eq_ignore_ascii_case(name))
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::LShift |
+            KeyCode::RShift |
+            KeyCode::LControl |
+            KeyCode::RControl |
+            KeyCode::LAlt |
+            KeyCode::RAlt |
+            KeyCode::LWin |
+            KeyCode::RWin
+    )
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub fn held(keyboard: &Keyboard) -> Self {
+        Modifiers {
+            ctrl: keyboard.ctrl(),
+            shift: keyboard.shift(),
+            alt: keyboard.alt(),
+        }
+    }
+
+    fn contains(&self, other: Modifiers) -> bool {
+        (self.ctrl || !other.ctrl) && (self.shift || !other.shift) && (self.alt || !other.alt)
+    }
+
+    //what holding `key` on its own counts as, so a modifier bound as a key still matches itself
+    fn of_key(key: KeyCode) -> Self {
+        Modifiers {
+            ctrl: matches!(key, KeyCode::LControl | KeyCode::RControl),
+            shift: matches!(key, KeyCode::LShift | KeyCode::RShift),
+            alt: matches!(key, KeyCode::LAlt | KeyCode::RAlt),
+        }
+    }
+
+    fn with(self, other: Modifiers) -> Self {
+        Modifiers { ctrl: self.ctrl || other.ctrl, shift: self.shift || other.shift, alt: self.alt || other.alt }
+    }
+}
+
+//a key plus the modifiers that have to be held with it, written like `ctrl+shift+S`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Binding {
+    pub key: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl Binding {
+    pub fn new(key: KeyCode) -> Self {
+        Binding { key, modifiers: Modifiers::default() }
+    }
+
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn parse(text: &str) -> Option<Binding> {
+        let mut modifiers = Modifiers::default();
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = key_from_name(parts.pop()?)?;
+        for part in parts {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" => modifiers.ctrl = true,
+                "shift" => modifiers.shift = true,
+                "alt" => modifiers.alt = true,
+                _ => {
+                    return None;
+                }
+            }
+        }
+        Some(Binding { key, modifiers })
+    }
+
+    //held actions ignore extra modifiers, so walking keeps going while shift is down
+    pub fn is_down(&self, keyboard: &Keyboard) -> bool {
+        keyboard.is_down(self.key) && Modifiers::held(keyboard).contains(self.modifiers)
+    }
+
+    //pressed actions need exactly these modifiers, so `P` and `ctrl+P` can do different things,
+    //the key itself being one of them does not count as extra
+    pub fn was_pressed(&self, keyboard: &Keyboard) -> bool {
+        keyboard.was_pressed(self.key) && Modifiers::held(keyboard) == self.modifiers.with(Modifiers::of_key(self.key))
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.modifiers.ctrl {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.shift {
+            write!(f, "shift+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "alt+")?;
+        }
+        write!(f, "{}", key_name(self.key))
+    }
+}
+
+//every action with its bindings, any of them triggers it
+pub struct KeyBindings {
+    bindings: Vec<(Action, Vec<Binding>)>,
+    pub gamepad: GamepadSettings,
+    pub movement_model: MovementModel,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            bindings: Action::ALL.into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+            gamepad: GamepadSettings::default(),
+            movement_model: MovementModel::default(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[], |(_, bindings)| bindings.as_slice())
+    }
+
+    fn get_mut(&mut self, action: Action) -> &mut Vec<Binding> {
+        let index = self.bindings
+            .iter()
+            .position(|(a, _)| *a == action)
+            .unwrap_or_else(|| {
+                self.bindings.push((action, Vec::new()));
+                self.bindings.len() - 1
+            });
+        &mut self.bindings[index].1
+    }
+
+    //adds a binding to `action`, taking it away from any other action first
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        for (_, bindings) in self.bindings.iter_mut() {
+            bindings.retain(|b| *b != binding);
+        }
+        self.get_mut(action).push(binding);
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.get_mut(action).clear();
+    }
+
+    pub fn is_down(&self, keyboard: &Keyboard, action: Action) -> bool {
+        self.get(action)
+            .iter()
+            .any(|binding| binding.is_down(keyboard))
+    }
+
+    pub fn was_pressed(&self, keyboard: &Keyboard, action: Action) -> bool {
+        self.get(action)
+            .iter()
+            .any(|binding| binding.was_pressed(keyboard))
+    }
+
+    //the lines that hold a setting instead of keys, false when `name` is an action
+    fn parse_setting(&mut self, name: &str, value: &str) -> Result<bool, String> {
+        if name == "movement_model" {
+            self.movement_model = MovementModel::from_name(value).ok_or_else(||
+                format!("movement_model is `facing` or `screen`, not `{}`", value)
+            )?;
+            return Ok(true);
+        }
+        let setting = match name {
+            "gamepad_deadzone" => &mut self.gamepad.deadzone,
+            "gamepad_aim_deadzone" => &mut self.gamepad.aim_deadzone,
+            "gamepad_sensitivity" => &mut self.gamepad.sensitivity,
+            _ => {
+                return Ok(false);
+            }
+        };
+        let number = value
+            .parse::<f32>()
+            .ok()
+            .filter(|number| *number >= 0.0)
+            .ok_or_else(|| format!("`{}` is not a number of 0 or more", value))?;
+        if name != "gamepad_sensitivity" && number >= 1.0 {
+            return Err(format!("{} has to be below 1", name));
+        }
+        *setting = number;
+        Ok(true)
+    }
+
+    //the action other than `action` that has `binding`
+    fn bound_elsewhere(&self, action: Action, binding: Binding) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(a, bindings)| *a != action && bindings.contains(&binding))
+            .map(|(a, _)| *a)
+    }
+
+    //one `action = binding, binding` per line, `#` starts a comment,
+    //actions missing from the text keep their defaults but lose keys the text gives to others,
+    //a key the text gives to two actions is an error
+    pub fn parse(text: &str) -> Result<KeyBindings, String> {
+        let mut parsed = KeyBindings::default();
+        let mut listed = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `action = keys`", i + 1))?;
+            if parsed.parse_setting(name.trim(), value.trim()).map_err(|e| format!("line {}: {}", i + 1, e))? {
+                continue;
+            }
+            let action = Action::from_name(name.trim()).ok_or_else(||
+                format!("line {}: unknown action `{}`", i + 1, name.trim())
+            )?;
+            let bindings = value
+                .split(',')
+                .map(str::trim)
+                .filter(|binding| !binding.is_empty())
+                .map(|binding|
+                    Binding::parse(binding).ok_or_else(||
+                        format!("line {}: unknown key `{}`", i + 1, binding)
+                    )
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+            parsed.clear(action);
+            for binding in bindings {
+                if let Some(other) = parsed.bound_elsewhere(action, binding).filter(|other| listed.contains(other)) {
+                    return Err(format!("line {}: `{}` is already bound to {}", i + 1, binding, other.name()));
+                }
+                parsed.bind(action, binding);
+            }
+            listed.push(action);
+        }
+        Ok(parsed)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# action = key, ctrl+shift+alt+key, ...\n");
+        for action in Action::ALL {
+            let bindings: Vec<String> = self
+                .get(action)
+                .iter()
+                .map(|binding| binding.to_string())
+                .collect();
+            text.push_str(&format!("{} = {}\n", action.name(), bindings.join(", ")));
+        }
+        text.push_str(&format!("movement_model = {}\n", self.movement_model.name()));
+        text.push_str(&format!("gamepad_deadzone = {}\n", self.gamepad.deadzone));
+        text.push_str(&format!("gamepad_aim_deadzone = {}\n", self.gamepad.aim_deadzone));
+        text.push_str(&format!("gamepad_sensitivity = {}\n", self.gamepad.sensitivity));
+        text
+    }
+
+    //a missing file is not an error, the defaults are used until the first save
+    pub fn load(path: impl AsRef<Path>) -> io::Result<KeyBindings> {
+        match std::fs::read_to_string(path) {
+            Ok(text) =>
+                KeyBindings::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(KeyBindings::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+}
+
+//the binding for the key that went down this frame, modifiers on their own do not count
+pub fn pressed_binding(keyboard: &Keyboard) -> Option<Binding> {
+    let mut pressed: Vec<KeyCode> = keyboard.pressed
+        .iter()
+        .copied()
+        .filter(|key| !is_modifier(*key) && KEYS.contains(key))
+        .collect();
+    pressed.sort();
+    pressed
+        .first()
+        .map(|key| Binding::new(*key).with_modifiers(Modifiers::held(keyboard)))
+}
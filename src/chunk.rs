@@ -1,45 +1,31 @@
 use crate::*;
-use notan::random::rand::{ self, random };
-
-const TEXTURE_SIZE: u32 = 1024;
-const BLOCK_SIZE: u32 = 64;
 
 pub struct Chunk {
     coords: (usize, usize),
-    floor_tiles: Vec<u8>,
-    wall_tiles: Vec<u8>,
+    //pixels across at full detail and of one tile in the atlas
+    texture_size: u32,
+    atlas_tile_size: u32,
     render_texture: RenderTexture,
     low_res: RenderTexture,
     level_of_detail: u8,
     lod: u8,
-    in_bounds: bool,
 }
 
-fn index_to_pos(i: usize, level_of_detail: u8) -> (f32, f32) {
-    (
-        (((i % 16) * BLOCK_SIZE as usize) / (level_of_detail as usize)) as f32,
-        (((i / 16) * BLOCK_SIZE as usize) / (level_of_detail as usize)) as f32,
-    )
+fn index_to_pos(i: usize, tile_size: f32) -> (f32, f32) {
+    (((i % 16) as f32) * tile_size, ((i / 16) as f32) * tile_size)
 }
 
 impl Chunk {
-    pub fn new(gfx: &mut Graphics, x: usize, y: usize) -> Self {
-        let mut floor_tiles = Vec::with_capacity(16 * 16);
-        let mut wall_tiles = Vec::with_capacity(16 * 16);
-        for i in 0..16 * 16 {
-            floor_tiles.push((random::<f32>() * 6.0) as u8);
-            wall_tiles.push(((random::<f32>() * 22.0) as u8));
-        }
-        let mut render_texture = gfx.create_render_texture(64, 64).build().unwrap();
+    pub fn new(gfx: &mut Graphics, x: usize, y: usize, settings: &RenderSettings) -> Self {
+        let render_texture = gfx.create_render_texture(64, 64).build().unwrap();
         Chunk {
             coords: (x, y),
-            floor_tiles,
-            wall_tiles,
+            texture_size: settings.chunk_texture_size,
+            atlas_tile_size: settings.atlas_tile_size,
             low_res: render_texture.clone(),
             render_texture,
             level_of_detail: 3,
             lod: 4,
-            in_bounds: false,
         }
     }
 
@@ -47,51 +33,70 @@ impl Chunk {
         self.level_of_detail = lod.clamp(0, 4);
     }
 
+    //the level of detail the chunk is drawn at once it is redrawn, 0 is full detail
+    pub fn level_of_detail(&self) -> u8 {
+        self.level_of_detail
+    }
+
+    //bytes of both render textures, four per pixel
+    pub fn texture_bytes(&self) -> usize {
+        [&self.render_texture, &self.low_res]
+            .iter()
+            .map(|texture| (texture.width() as usize) * (texture.height() as usize) * 4)
+            .sum()
+    }
+
     //lod = level of detail
-    pub fn render_low_res(&mut self, gfx: &mut Graphics, textures: &Vec<Texture>) {
+    pub fn render_low_res(&mut self, gfx: &mut Graphics, textures: &[Texture], tiles: &ChunkTiles) {
         let lod = self.lod;
         self.lod = 4;
-        self.low_res = self.render_texture(gfx, textures);
+        self.low_res = self.render_texture(gfx, textures, tiles);
         self.lod = lod;
     }
 
-    fn render_texture(&mut self, gfx: &mut Graphics, textures: &Vec<Texture>) -> RenderTexture {
-        let lod = (2_u32).pow(self.lod as u32);
+    fn render_texture(&mut self, gfx: &mut Graphics, textures: &[Texture], tiles: &ChunkTiles) -> RenderTexture {
+        self.render_at(gfx, textures, tiles, self.lod)
+    }
+
+    //the tiles at any level of detail, without changing what the chunk shows
+    pub fn render_at(&self, gfx: &mut Graphics, textures: &[Texture], tiles: &ChunkTiles, lod: u8) -> RenderTexture {
+        let lod = (2_u32).pow(lod as u32);
+        let texture_size = self.texture_size / lod;
         let size = gfx.size();
-        gfx.set_size(TEXTURE_SIZE / lod, TEXTURE_SIZE / lod);
-        let mut texture = gfx
-            .create_render_texture(TEXTURE_SIZE / lod, TEXTURE_SIZE / lod)
+        gfx.set_size(texture_size, texture_size);
+        let texture = gfx
+            .create_render_texture(texture_size, texture_size)
             .build()
             .unwrap();
 
         let mut draw = gfx.create_draw();
+        let tile_size = (texture_size as f32) / (CHUNK_TILES as f32);
+        let atlas_tile = self.atlas_tile_size as f32;
         for i in 0..256 {
-            let (x, y) = index_to_pos(i, lod as u8);
-            let b = match self.wall_tiles[i] {
-                x if x > 16 => { x }
-                _ => { self.floor_tiles[i] }
-            };
+            let (x, y) = index_to_pos(i, tile_size);
+            let b = tiles.visible_tile(i);
             draw.image(&textures[0])
                 .position(x, y)
-                .size(BLOCK_SIZE as f32 / (lod as f32), BLOCK_SIZE as f32 / (lod as f32))
-                .crop((((b % 16) as f32) * BLOCK_SIZE as f32, (b / 16) as f32 * BLOCK_SIZE as f32), (BLOCK_SIZE as f32, BLOCK_SIZE as f32));
+                .size(tile_size, tile_size)
+                .crop((((b % 16) as f32) * atlas_tile, (b / 16) as f32 * atlas_tile), (atlas_tile, atlas_tile));
         }
-        self.floor_tiles
-            .iter()
-            .enumerate()
-            .for_each(|(i, b)| {});
-        gfx.render_to(&mut texture, &draw);
+        gfx.render_to(&texture, &draw);
         gfx.set_size(size.0, size.1);
         texture
     }
 
+    //the tiles changed, render again at the current level of detail
+    pub fn invalidate(&mut self) {
+        self.lod = u8::MAX;
+    }
+
     pub fn needs_redraw(&self) -> bool {
         self.lod != self.level_of_detail
     }
 
-    pub fn redraw(&mut self, gfx: &mut Graphics, textures: &Vec<Texture>) {
+    pub fn redraw(&mut self, gfx: &mut Graphics, textures: &[Texture], tiles: &ChunkTiles) {
         self.lod = self.level_of_detail;
-        self.render_texture = self.render_texture(gfx, textures);
+        self.render_texture = self.render_texture(gfx, textures, tiles);
     }
 
     fn coords_to_position(coords: (usize, usize)) -> Vec2 {
@@ -110,9 +115,30 @@ impl Chunk {
         256
     }
 
+    pub fn tile_size() -> usize {
+        Chunk::size() / 16
+    }
+
+    //whether `pos` is on a world `area_size` chunks across
+    pub fn in_world(pos: Vec2, area_size: usize) -> bool {
+        let size = (area_size * Chunk::size()) as f32;
+        (0.0..size).contains(&pos.x) && (0.0..size).contains(&pos.y)
+    }
+
+    pub fn pos_to_tile(pos: Vec2) -> (usize, usize) {
+        ((pos.x as usize) / Chunk::tile_size(), (pos.y as usize) / Chunk::tile_size())
+    }
+
+    pub fn tile_center(tile: (usize, usize)) -> Vec2 {
+        let half = (Chunk::tile_size() as f32) / 2.0;
+        Vec2::new(
+            ((tile.0 * Chunk::tile_size()) as f32) + half,
+            ((tile.1 * Chunk::tile_size()) as f32) + half
+        )
+    }
+
     pub fn render(&self, draw: &mut Draw, debug: bool) {
         let (x, y) = Chunk::coords_to_position(self.coords).into();
-        let (x2, y2) = Chunk::coords_to_position((self.coords.0, self.coords.1)).into();
 
         let screen_pos = draw.world_to_screen_position(x, y);
         let screen_pos2 = draw.world_to_screen_position(
@@ -135,7 +161,7 @@ impl Chunk {
                 Color::new(
                     1.0 / (5.0 - (self.lod as f32)),
                     1.0,
-                    self.render_texture.width() / 1024.0,
+                    self.render_texture.width() / (self.texture_size as f32),
                     1.0
                 )
             );
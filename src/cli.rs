@@ -0,0 +1,239 @@
+use crate::*;
+
+pub const USAGE: &str = "\
+usage: indifi [options]                 play
+       indifi generate <file> [world]   write a world to disk
+       indifi stats [world]             print what a world is made of
+       indifi help                      show this
+
+options:
+  --seed <number>            world seed when playing alone
+  --world <file>             play on a world written by `generate`
+  --position <x,y>           start there instead of where the profile left off
+  --size <width>x<height>    window size
+  --fullscreen               start in fullscreen
+  --config <file>            settings file, indifi.toml by default, reloaded when it changes
+  --assets <dir>             read art from here and reload it when it changes,
+                             debug builds use src/assets, release builds the built in art
+  --set <section.name=value> override one setting, like --set render.zoom=10
+  --connect <address>        join a server
+  --name <name>              play as this profile, made when there is none
+  --profiles <dir>           where profiles are kept, ./profiles by default
+  --tick-rate <hz>           simulation steps per second when playing alone
+  --record <file>            save the session's input on exit
+  --replay <file>            play a recorded session back
+  --bindings <file>          key bindings file, bindings.cfg by default
+  --facing <four|eight|free> directions players are drawn facing
+
+world, for generate and stats:
+  --seed <number>            random when left out
+  --area-size <chunks>       width and height, from the settings file when left out
+  --world <file>             stats only, read the world from a file
+  --config <file>            settings file for the area size";
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayArgs {
+    pub connect: Option<String>,
+    pub name: Option<String>,
+    pub profiles: String,
+    pub tick_rate: f32,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+    pub bindings: String,
+    pub facing: Option<Facing>,
+    pub config: String,
+    //None uses the art built into the binary
+    pub assets: Option<String>,
+    //`section.name` and value of every --set, --size and --fullscreen, applied over the
+    //config file in order
+    pub overrides: Vec<(String, String)>,
+    pub seed: Option<u64>,
+    pub world: Option<String>,
+    pub position: Option<Vec2>,
+}
+
+impl Default for PlayArgs {
+    fn default() -> Self {
+        PlayArgs {
+            connect: None,
+            name: None,
+            profiles: DEFAULT_PROFILES_DIR.to_string(),
+            tick_rate: DEFAULT_TICK_RATE,
+            record: None,
+            replay: None,
+            bindings: DEFAULT_BINDINGS_PATH.to_string(),
+            facing: None,
+            config: DEFAULT_CONFIG_PATH.to_string(),
+            assets: cfg!(debug_assertions).then(|| SOURCE_ASSETS_DIR.to_string()),
+            overrides: Vec::new(),
+            seed: None,
+            world: None,
+            position: None,
+        }
+    }
+}
+
+//which world generate and stats work on
+#[derive(Clone, PartialEq, Debug)]
+pub struct WorldArgs {
+    pub seed: Option<u64>,
+    pub area_size: Option<usize>,
+    pub world: Option<String>,
+    pub config: String,
+}
+
+impl Default for WorldArgs {
+    fn default() -> Self {
+        WorldArgs {
+            seed: None,
+            area_size: None,
+            world: None,
+            config: DEFAULT_CONFIG_PATH.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Play(PlayArgs),
+    Generate {
+        path: String,
+        world: WorldArgs,
+    },
+    Stats(WorldArgs),
+    Help,
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("{} needs a value", flag))
+}
+
+fn number<T: std::str::FromStr>(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<T, String> {
+    let text = value(args, flag)?;
+    text.parse().map_err(|_| format!("{} takes a number, not `{}`", flag, text))
+}
+
+//`x,y`
+fn position(text: &str) -> Option<Vec2> {
+    let (x, y) = text.split_once(',')?;
+    Some(Vec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?)).filter(|position| position.is_finite())
+}
+
+//`widthxheight`
+fn size(text: &str) -> Option<(u32, u32)> {
+    let (width, height) = text.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+//the arguments after the program name, errors name the option that was wrong
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("help" | "--help" | "-h") => Ok(Command::Help),
+        Some("generate") => {
+            args.next();
+            let path = args.next().filter(|path| !path.starts_with("--")).ok_or("generate needs a file to write")?;
+            Ok(Command::Generate { path, world: parse_world(args)? })
+        }
+        Some("stats") => {
+            args.next();
+            Ok(Command::Stats(parse_world(args)?))
+        }
+        _ => parse_play(args).map(Command::Play),
+    }
+}
+
+fn parse_world(mut args: impl Iterator<Item = String>) -> Result<WorldArgs, String> {
+    let mut parsed = WorldArgs::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                parsed.seed = Some(number(&mut args, &arg)?);
+            }
+            "--area-size" => {
+                parsed.area_size = Some(number(&mut args, &arg)?);
+            }
+            "--world" => {
+                parsed.world = Some(value(&mut args, &arg)?);
+            }
+            "--config" => {
+                parsed.config = value(&mut args, &arg)?;
+            }
+            _ => {
+                return Err(format!("unknown option `{}`", arg));
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+fn parse_play(mut args: impl Iterator<Item = String>) -> Result<PlayArgs, String> {
+    let mut parsed = PlayArgs::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--connect" => {
+                parsed.connect = Some(value(&mut args, &arg)?);
+            }
+            "--name" => {
+                parsed.name = Some(value(&mut args, &arg)?);
+            }
+            "--profiles" => {
+                parsed.profiles = value(&mut args, &arg)?;
+            }
+            "--record" => {
+                parsed.record = Some(value(&mut args, &arg)?);
+            }
+            "--replay" => {
+                parsed.replay = Some(value(&mut args, &arg)?);
+            }
+            "--bindings" => {
+                parsed.bindings = value(&mut args, &arg)?;
+            }
+            "--config" => {
+                parsed.config = value(&mut args, &arg)?;
+            }
+            "--assets" => {
+                parsed.assets = Some(value(&mut args, &arg)?);
+            }
+            "--set" => {
+                let set = value(&mut args, &arg)?;
+                let (key, value) = set.split_once('=').ok_or_else(|| format!("--set takes name=value, not `{}`", set))?;
+                parsed.overrides.push((key.trim().to_string(), value.to_string()));
+            }
+            "--size" => {
+                let text = value(&mut args, &arg)?;
+                let (width, height) = size(&text).ok_or_else(|| format!("--size takes 1280x720, not `{}`", text))?;
+                parsed.overrides.push(("window.width".to_string(), width.to_string()));
+                parsed.overrides.push(("window.height".to_string(), height.to_string()));
+            }
+            "--fullscreen" => {
+                parsed.overrides.push(("window.fullscreen".to_string(), "true".to_string()));
+            }
+            "--facing" => {
+                let name = value(&mut args, &arg)?;
+                parsed.facing = Some(
+                    Facing::from_name(&name).ok_or_else(|| format!("--facing is four, eight or free, not `{}`", name))?
+                );
+            }
+            "--tick-rate" => {
+                parsed.tick_rate = Some(number::<f32>(&mut args, &arg)?)
+                    .filter(|rate| *rate > 0.0)
+                    .ok_or("--tick-rate has to be above 0")?;
+            }
+            "--seed" => {
+                parsed.seed = Some(number(&mut args, &arg)?);
+            }
+            "--world" => {
+                parsed.world = Some(value(&mut args, &arg)?);
+            }
+            "--position" => {
+                let text = value(&mut args, &arg)?;
+                parsed.position = Some(position(&text).ok_or_else(|| format!("--position takes x,y, not `{}`", text))?);
+            }
+            _ => {
+                return Err(format!("unknown option `{}`", arg));
+            }
+        }
+    }
+    Ok(parsed)
+}
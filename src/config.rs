@@ -0,0 +1,261 @@
+use crate::*;
+use serde::{ Deserialize, Serialize };
+use std::io;
+use std::path::Path;
+use toml_edit::{ DocumentMut, Item, TomlError };
+
+pub const DEFAULT_CONFIG_PATH: &str = "indifi.toml";
+//biggest zoom the projection still handles, 2^20 units across the screen
+const MAX_ZOOM: u8 = 20;
+//biggest world in chunks per side, bigger ones take too long to generate and render
+pub const MAX_AREA_SIZE: usize = 64;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowSettings {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub high_dpi: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        WindowSettings {
+            width: 800,
+            height: 600,
+            fullscreen: false,
+            vsync: false,
+            high_dpi: true,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderSettings {
+    //the screen is 2^zoom world units wide, new profiles start at `zoom`
+    pub zoom: u8,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    //pixels of a chunk texture at full detail and of one tile in the atlas
+    pub chunk_texture_size: u32,
+    pub atlas_tile_size: u32,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            zoom: 8,
+            min_zoom: 6,
+            max_zoom: 15,
+            chunk_texture_size: 1024,
+            atlas_tile_size: 64,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldSettings {
+    //width and height in chunks
+    pub area_size: usize,
+    //where players without a saved position appear
+    #[serde(with = "position")]
+    pub player_start: Vec2,
+}
+
+impl Default for WorldSettings {
+    fn default() -> Self {
+        WorldSettings {
+            area_size: AREA_SIZE,
+            player_start: Vec2::new(2048.0, 2048.0),
+        }
+    }
+}
+
+//settings read at startup from indifi.toml, everything missing keeps its default
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub window: WindowSettings,
+    pub render: RenderSettings,
+    pub world: WorldSettings,
+}
+
+//positions are written as `[x, y]`
+mod position {
+    use notan::math::Vec2;
+    use serde::{ Deserialize, Deserializer, Serialize, Serializer };
+
+    pub fn serialize<S: Serializer>(position: &Vec2, serializer: S) -> Result<S::Ok, S::Error> {
+        [position.x, position.y].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec2, D::Error> {
+        let [x, y] = <[f32; 2]>::deserialize(deserializer)?;
+        Ok(Vec2::new(x, y))
+    }
+}
+
+//one value the way it is written after `name = ` in the file
+fn parse_value(value: &str) -> Result<toml::Value, String> {
+    let value = value.trim();
+    format!("value = {}", value)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .ok_or_else(|| format!("`{}` is not a number, true or false, or a position like [2048, 2048]", value))
+}
+
+impl Config {
+    //sets one setting by its `section.name`, used for --set and the settings screen
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let mut table = toml::Table::try_from(&*self).map_err(|e| e.to_string())?;
+        let setting = key
+            .split_once('.')
+            .and_then(|(section, name)| table.get_mut(section)?.as_table_mut()?.get_mut(name))
+            .ok_or_else(|| format!("unknown setting `{}`", key))?;
+        *setting = parse_value(value)?;
+        *self = table.try_into().map_err(|e: toml::de::Error| e.message().to_string())?;
+        Ok(())
+    }
+
+    //the text of indifi.toml, errors start with the line they are on
+    pub fn parse(text: &str) -> Result<Config, String> {
+        toml::from_str(text).map_err(|e| {
+            match e.span() {
+                Some(span) => format!("line {}: {}", text[..span.start].matches('\n').count() + 1, e.message()),
+                None => e.message().to_string(),
+            }
+        })
+    }
+
+    //every reason the settings can not be used, checked after the file and the overrides
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if self.window.width == 0 || self.window.height == 0 {
+            errors.push(
+                format!("window is {}x{}, both sides have to be above 0", self.window.width, self.window.height)
+            );
+        }
+        let render = &self.render;
+        if render.min_zoom > render.max_zoom || render.max_zoom > MAX_ZOOM {
+            errors.push(
+                format!(
+                    "render.min_zoom {} and render.max_zoom {} have to be in order and at most {}",
+                    render.min_zoom,
+                    render.max_zoom,
+                    MAX_ZOOM
+                )
+            );
+        } else if !(render.min_zoom..=render.max_zoom).contains(&render.zoom) {
+            errors.push(
+                format!(
+                    "render.zoom is {}, it has to be between min_zoom {} and max_zoom {}",
+                    render.zoom,
+                    render.min_zoom,
+                    render.max_zoom
+                )
+            );
+        }
+        //every lod halves the texture and a chunk is 16 tiles across
+        let min_texture = (CHUNK_TILES as u32) * 16;
+        if !render.chunk_texture_size.is_power_of_two() || render.chunk_texture_size < min_texture {
+            errors.push(
+                format!(
+                    "render.chunk_texture_size is {}, it has to be a power of two of at least {}",
+                    render.chunk_texture_size,
+                    min_texture
+                )
+            );
+        }
+        if render.atlas_tile_size == 0 {
+            errors.push("render.atlas_tile_size has to be above 0".to_string());
+        }
+        if !(1..=MAX_AREA_SIZE).contains(&self.world.area_size) {
+            errors.push(
+                format!("world.area_size is {}, it has to be between 1 and {}", self.world.area_size, MAX_AREA_SIZE)
+            );
+        } else {
+            let start = self.world.player_start;
+            if !Chunk::in_world(start, self.world.area_size) {
+                errors.push(
+                    format!(
+                        "world.player_start [{}, {}] is outside of the world, which is {} across",
+                        start.x,
+                        start.y,
+                        self.world.area_size * Chunk::size()
+                    )
+                );
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    //a missing file is not an error, the defaults are used instead
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Config> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    //every setting, in the format parse reads
+    pub fn to_text(&self) -> String {
+        toml::to_string(self).expect("the settings are only numbers and flags")
+    }
+
+    //`text` with each `section.name` in `changes` set to its value, comments and every other line
+    //stay as they are, settings the text does not have yet go at the end of their section
+    pub fn edit_text(text: &str, changes: &[(String, String)]) -> Result<String, String> {
+        let mut document: DocumentMut = text.parse().map_err(|e: TomlError| e.message().to_string())?;
+        for (key, value) in changes {
+            let (section, name) = key.split_once('.').ok_or_else(|| format!("unknown setting `{}`", key))?;
+            let mut value: toml_edit::Value = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("`{}` is not a number, true or false, or a position", value.trim()))?;
+            let empty = document.is_empty();
+            let table = document
+                .entry(section)
+                .or_insert_with(|| {
+                    let mut table = toml_edit::Table::new();
+                    //a blank line between it and the section before
+                    if !empty {
+                        table.decor_mut().set_prefix("\n");
+                    }
+                    Item::Table(table)
+                })
+                .as_table_mut()
+                .ok_or_else(|| format!("`{}` is not a section", section))?;
+            match table.get_mut(name).and_then(Item::as_value_mut) {
+                Some(old) => {
+                    //the spaces and the comment around the old value
+                    *value.decor_mut() = old.decor().clone();
+                    *old = value;
+                }
+                None => {
+                    table.insert(name, Item::Value(value));
+                }
+            }
+        }
+        Ok(document.to_string())
+    }
+
+    //comments in the file are not kept, edit_text keeps them
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    //the zoom a profile saved, kept inside the configured limits
+    pub fn clamp_zoom(&self, zoom: u8) -> u8 {
+        zoom.clamp(self.render.min_zoom, self.render.max_zoom)
+    }
+}
@@ -0,0 +1,297 @@
+use crate::*;
+use std::collections::VecDeque;
+
+//lines the console keeps, older ones scroll away
+const OUTPUT_LINES: usize = 200;
+const LINE_HEIGHT: f32 = 18.0;
+const TEXT_SIZE: f32 = 14.0;
+//part of the screen the console drops down over
+const HEIGHT_PART: f32 = 0.4;
+
+//what a command prints, or why it could not run
+pub type CommandResult = Result<String, String>;
+
+pub struct Command<C> {
+    pub name: &'static str,
+    //the arguments, like `<x> <y>`
+    pub usage: &'static str,
+    pub help: &'static str,
+    run: fn(&mut C, &[String]) -> CommandResult,
+}
+
+//a value the console can show and change by name
+pub struct Variable<C> {
+    pub name: &'static str,
+    pub help: &'static str,
+    get: fn(&C) -> String,
+    set: fn(&mut C, &str) -> Result<(), String>,
+}
+
+//the commands and variables of a console working on a `C`, every module can add its own,
+//`help` and `set` are always there
+pub struct CommandRegistry<C> {
+    commands: Vec<Command<C>>,
+    variables: Vec<Variable<C>>,
+}
+
+impl<C> Default for CommandRegistry<C> {
+    fn default() -> Self {
+        CommandRegistry { commands: Vec::new(), variables: Vec::new() }
+    }
+}
+
+impl<C> CommandRegistry<C> {
+    pub fn new() -> Self {
+        CommandRegistry::default()
+    }
+
+    //a later command with the same name replaces the earlier one
+    pub fn command(
+        mut self,
+        name: &'static str,
+        usage: &'static str,
+        help: &'static str,
+        run: fn(&mut C, &[String]) -> CommandResult
+    ) -> Self {
+        self.commands.retain(|command| command.name != name);
+        self.commands.push(Command { name, usage, help, run });
+        self
+    }
+
+    pub fn variable(
+        mut self,
+        name: &'static str,
+        help: &'static str,
+        get: fn(&C) -> String,
+        set: fn(&mut C, &str) -> Result<(), String>
+    ) -> Self {
+        self.variables.retain(|variable| variable.name != name);
+        self.variables.push(Variable { name, help, get, set });
+        self
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = &Command<C>> {
+        self.commands.iter()
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = &Variable<C>> {
+        self.variables.iter()
+    }
+
+    fn find_variable(&self, name: &str) -> Option<&Variable<C>> {
+        self.variables.iter().find(|variable| variable.name == name)
+    }
+
+    //runs a command line, a variable on its own prints its value and with a value sets it
+    pub fn run(&self, context: &mut C, line: &str) -> CommandResult {
+        let words = split_words(line)?;
+        let Some((name, args)) = words.split_first() else {
+            return Ok(String::new());
+        };
+        match name.as_str() {
+            "help" => self.help(args.first().map(String::as_str)),
+            "set" =>
+                match args {
+                    [name, value] => self.set(context, name, value),
+                    _ => Err("usage: set <variable> <value>".to_string()),
+                }
+            _ => {
+                if let Some(command) = self.commands.iter().find(|command| command.name == name) {
+                    return (command.run)(context, args);
+                }
+                match (self.find_variable(name), args) {
+                    (Some(variable), []) => Ok(format!("{} = {}", variable.name, (variable.get)(context))),
+                    (Some(_), [value]) => self.set(context, name, value),
+                    (Some(variable), _) => Err(format!("usage: {} [value]", variable.name)),
+                    (None, _) => Err(format!("unknown command `{}`, `help` lists them", name)),
+                }
+            }
+        }
+    }
+
+    fn set(&self, context: &mut C, name: &str, value: &str) -> CommandResult {
+        let variable = self.find_variable(name).ok_or_else(|| format!("unknown variable `{}`", name))?;
+        (variable.set)(context, value).map_err(|e| format!("{}: {}", name, e))?;
+        Ok(format!("{} = {}", variable.name, (variable.get)(context)))
+    }
+
+    //every command and variable, or the usage of one
+    fn help(&self, name: Option<&str>) -> CommandResult {
+        let mut lines = Vec::new();
+        for command in self.commands.iter().filter(|command| name.is_none_or(|name| command.name == name)) {
+            lines.push(format!("{} {} - {}", command.name, command.usage, command.help));
+        }
+        for variable in self.variables.iter().filter(|variable| name.is_none_or(|name| variable.name == name)) {
+            lines.push(format!("{} [value] - {}", variable.name, variable.help));
+        }
+        match name {
+            None => {
+                lines.insert(0, "help [name] - this list".to_string());
+                lines.insert(1, "set <variable> <value> - changes a variable".to_string());
+            }
+            Some(name) if lines.is_empty() => {
+                return Err(format!("there is no `{}`", name));
+            }
+            _ => {}
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+//words split at spaces, double quotes keep spaces in a word
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quoted {
+        return Err("a quote is not closed".to_string());
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+//the argument at `i` read as a T, the errors name it
+pub fn parse_arg<T: std::str::FromStr>(args: &[String], i: usize, name: &str) -> Result<T, String> {
+    let arg = args.get(i).ok_or_else(|| format!("missing <{}>", name))?;
+    arg.parse().map_err(|_| format!("<{}> can not be `{}`", name, arg))
+}
+
+//the line being typed, what came out of the commands and the lines entered before
+#[derive(Default)]
+pub struct Console {
+    input: String,
+    output: VecDeque<String>,
+    history: Vec<String>,
+    //how far up the history up and down went, None while typing a new line
+    browsing: Option<usize>,
+}
+
+impl Console {
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    //oldest first
+    pub fn output(&self) -> impl Iterator<Item = &str> {
+        self.output.iter().map(String::as_str)
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.output.len() == OUTPUT_LINES {
+                self.output.pop_front();
+            }
+            self.output.push_back(line.to_string());
+        }
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        self.input.extend(text.chars().filter(|c| !c.is_control()));
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    //the line entered before the one shown
+    pub fn history_up(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+        let i = match self.browsing {
+            Some(i) => i.saturating_sub(1),
+            None => self.history.len() - 1,
+        };
+        self.browsing = Some(i);
+        self.input = self.history[i].clone();
+    }
+
+    //the line entered after the one shown, an empty line past the last one
+    pub fn history_down(&mut self) {
+        let Some(i) = self.browsing else {
+            return;
+        };
+        if i + 1 < self.history.len() {
+            self.browsing = Some(i + 1);
+            self.input = self.history[i + 1].clone();
+        } else {
+            self.browsing = None;
+            self.input.clear();
+        }
+    }
+
+    //runs the typed line and prints it with whatever it printed
+    pub fn submit<C>(&mut self, registry: &CommandRegistry<C>, context: &mut C) {
+        let line = std::mem::take(&mut self.input);
+        self.browsing = None;
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+        }
+        self.print(&format!("> {}", line));
+        match registry.run(context, line) {
+            Ok(text) => self.print(&text),
+            Err(e) => self.print(&format!("error: {}", e)),
+        }
+    }
+
+    //takes the keys and text the ui gets
+    pub fn update<C>(&mut self, input: &UiInput, registry: &CommandRegistry<C>, context: &mut C) {
+        self.type_text(&input.text);
+        for key in &input.keys {
+            match key {
+                KeyCode::Back => self.backspace(),
+                KeyCode::Up => self.history_up(),
+                KeyCode::Down => self.history_down(),
+                KeyCode::Return => self.submit(registry, context),
+                _ => {}
+            }
+        }
+    }
+
+    //drops down from the top of the screen, the newest output right above the input line
+    pub fn render(&self, draw: &mut Draw, font: &Font) {
+        let (width, height) = draw.size();
+        let height = (height * HEIGHT_PART).floor();
+        draw.rect((0.0, 0.0), (width, height)).color(Color::new(0.0, 0.0, 0.0, 0.8));
+        let input_y = height - LINE_HEIGHT - 4.0;
+        draw.text(font, &format!("> {}_", self.input))
+            .position(8.0, input_y)
+            .size(TEXT_SIZE)
+            .color(Color::YELLOW);
+        let rows = ((input_y / LINE_HEIGHT) as usize).saturating_sub(1);
+        for (i, line) in self.output.iter().rev().take(rows).enumerate() {
+            draw.text(font, line)
+                .position(8.0, input_y - ((i + 1) as f32) * LINE_HEIGHT)
+                .size(TEXT_SIZE);
+        }
+    }
+}
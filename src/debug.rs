@@ -0,0 +1,92 @@
+use crate::*;
+use std::collections::VecDeque;
+
+//frames the graph of the debug overlay shows
+pub const FRAME_HISTORY: usize = 120;
+//most levels of detail a chunk has, see Chunk::set_lod
+pub const LOD_LEVELS: usize = 5;
+
+//the last frame times in milliseconds, the oldest is dropped once it is full
+pub struct FrameTimes {
+    times: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl Default for FrameTimes {
+    fn default() -> Self {
+        FrameTimes::new(FRAME_HISTORY)
+    }
+}
+
+impl FrameTimes {
+    pub fn new(capacity: usize) -> Self {
+        FrameTimes { times: VecDeque::with_capacity(capacity), capacity: capacity.max(1) }
+    }
+
+    pub fn push(&mut self, ms: f32) {
+        if self.times.len() == self.capacity {
+            self.times.pop_front();
+        }
+        self.times.push_back(ms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    //oldest first
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = f32> + '_ {
+        self.times.iter().copied()
+    }
+
+    pub fn max(&self) -> f32 {
+        self.iter().fold(0.0, f32::max)
+    }
+
+    pub fn average(&self) -> f32 {
+        if self.times.is_empty() {
+            return 0.0;
+        }
+        self.iter().sum::<f32>() / (self.times.len() as f32)
+    }
+}
+
+//what the chunks of a world are doing, for the debug overlay
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ChunkStats {
+    //chunks by the level of detail they want, full detail first
+    pub per_lod: [usize; LOD_LEVELS],
+    //chunks waiting to be drawn again at their level of detail
+    pub pending: usize,
+    pub texture_bytes: usize,
+}
+
+impl ChunkStats {
+    pub fn of(chunks: &[Chunk]) -> Self {
+        let mut stats = ChunkStats::default();
+        for chunk in chunks {
+            stats.per_lod[(chunk.level_of_detail() as usize).min(LOD_LEVELS - 1)] += 1;
+            if chunk.needs_redraw() {
+                stats.pending += 1;
+            }
+            stats.texture_bytes += chunk.texture_bytes();
+        }
+        stats
+    }
+}
+
+//1536 -> "1.5 KiB"
+pub fn format_bytes(bytes: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f32;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", size, units[unit]) }
+}
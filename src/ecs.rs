@@ -0,0 +1,196 @@
+use crate::*;
+
+//an index into every storage. despawned ids are handed out again by spawn and there is no
+//generation to tell them apart, so an id kept after its entity was despawned points at
+//whatever was spawned next, check is_alive or drop the id on despawn
+pub type Entity = usize;
+
+pub struct Storage<T> {
+    items: Vec<Option<T>>,
+}
+
+impl<T> Default for Storage<T> {
+    fn default() -> Self {
+        Storage { items: Vec::new() }
+    }
+}
+
+impl<T> Storage<T> {
+    pub fn insert(&mut self, entity: Entity, item: T) {
+        if entity >= self.items.len() {
+            self.items.resize_with(entity + 1, || None);
+        }
+        self.items[entity] = Some(item);
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        self.items.get_mut(entity).and_then(|item| item.take())
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.items.get(entity).and_then(|item| item.as_ref())
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.items.get_mut(entity).and_then(|item| item.as_mut())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(entity, item)| item.as_ref().map(|item| (entity, item)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.items
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(entity, item)| item.as_mut().map(|item| (entity, item)))
+    }
+}
+
+pub enum Renderable {
+    Circle {
+        color: Color,
+    },
+    Sprite {
+        texture: Texture,
+        //position and size of the sprite inside the texture, the whole texture if None
+        crop: Option<((f32, f32), (f32, f32))>,
+        size: f32,
+    },
+    //a frame of `sheet` picked from the facing and the animation, tinted with `color`
+    Animated {
+        texture: Texture,
+        sheet: SpriteSheet,
+        animation: Animation,
+        color: Color,
+        size: f32,
+    },
+}
+
+impl Renderable {
+    pub fn render(&self, draw: &mut Draw, pos: Vec2, degrees: f32) {
+        let (x, y) = (pos.x, pos.y);
+        match self {
+            Renderable::Circle { color } => {
+                render_body(draw, (x, y), degrees, *color);
+            }
+            Renderable::Sprite { texture, crop, size } => {
+                let mut image = draw.image(texture);
+                image
+                    .position(x - size / 2.0, y - size / 2.0)
+                    .size(*size, *size)
+                    .rotate_degrees_from((x, y), degrees + 90.0);
+                if let Some((pos, crop_size)) = crop {
+                    image.crop(*pos, *crop_size);
+                }
+            }
+            Renderable::Animated { texture, sheet, animation, color, size } => {
+                let (crop_pos, crop_size, turn) = sheet.frame(animation, Angle::from_degrees(degrees));
+                draw.image(texture)
+                    .crop(crop_pos, crop_size)
+                    .position(x - size / 2.0, y - size / 2.0)
+                    .size(*size, *size)
+                    .rotate_degrees_from((x, y), turn)
+                    .color(*color);
+            }
+        }
+    }
+
+    //the tint of circles and animated sprites
+    pub fn color(&self) -> Option<Color> {
+        match self {
+            Renderable::Circle { color } | Renderable::Animated { color, .. } => Some(*color),
+            Renderable::Sprite { .. } => None,
+        }
+    }
+}
+
+//entity mirrored from a server snapshot, `id` is the player id there
+pub struct Networked {
+    pub id: u16,
+}
+
+pub struct Collider {
+    pub radius: f32,
+}
+
+//what an entity wants to do this tick, written by the input and ai systems
+pub struct Intent {
+    pub movement: Vec2,
+    pub desired_rotation: Angle,
+}
+
+impl Default for Intent {
+    fn default() -> Self {
+        Intent {
+            movement: Vec2::new(0.0, 0.0),
+            desired_rotation: Angle::ZERO,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct World {
+    alive: Vec<bool>,
+    free: Vec<Entity>,
+    pub transforms: Storage<TransformPR>,
+    pub intents: Storage<Intent>,
+    pub renderables: Storage<Renderable>,
+    pub inputs: Storage<PlayerInput>,
+    pub colliders: Storage<Collider>,
+    pub behaviours: Storage<Behaviour>,
+    pub players: Storage<Player>,
+    pub networked: Storage<Networked>,
+    pub spatial: SpatialIndex,
+}
+
+impl World {
+    pub fn new() -> Self {
+        World::default()
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        match self.free.pop() {
+            Some(entity) => {
+                self.alive[entity] = true;
+                entity
+            }
+            None => {
+                self.alive.push(true);
+                self.alive.len() - 1
+            }
+        }
+    }
+
+    pub fn despawn(&mut self, entity: Entity) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        self.alive[entity] = false;
+        self.free.push(entity);
+        self.transforms.remove(entity);
+        self.intents.remove(entity);
+        self.renderables.remove(entity);
+        self.inputs.remove(entity);
+        self.colliders.remove(entity);
+        self.behaviours.remove(entity);
+        self.players.remove(entity);
+        self.networked.remove(entity);
+        self.spatial.remove(entity);
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.alive.get(entity).copied().unwrap_or(false)
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive
+            .iter()
+            .enumerate()
+            .filter(|(_, alive)| **alive)
+            .map(|(entity, _)| entity)
+    }
+}
@@ -0,0 +1,259 @@
+use crate::*;
+use std::fs::File;
+use std::io::{ self, Write };
+use std::path::{ Path, PathBuf };
+
+//widest and tallest image an export may make, it is written a row of chunks at a time so only
+//one strip this wide is ever in memory
+pub const MAX_EXPORT_SIDE: u32 = 16384;
+const BYTES_PER_PIXEL: usize = 4;
+
+//rgba pixels in rows from the top, put together on the cpu since the whole world does not
+//fit in one texture
+#[derive(Clone, PartialEq, Debug)]
+pub struct PixelImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl PixelImage {
+    //transparent black
+    pub fn new(width: u32, height: u32) -> Self {
+        PixelImage { width, height, pixels: vec![0; (width as usize) * (height as usize) * BYTES_PER_PIXEL] }
+    }
+
+    //`pixels` are rows from the top unless `bottom_up`, render textures read back bottom row first
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<u8>, bottom_up: bool) -> Result<Self, String> {
+        let mut image = PixelImage { width, height, pixels };
+        if image.pixels.len() != (width as usize) * (height as usize) * BYTES_PER_PIXEL {
+            return Err(format!("{} bytes are not a {}x{} image", image.pixels.len(), width, height));
+        }
+        if bottom_up {
+            image.flip_rows();
+        }
+        Ok(image)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = ((x as usize) + (y as usize) * (self.width as usize)) * BYTES_PER_PIXEL;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[i..i + BYTES_PER_PIXEL]);
+        Some(pixel)
+    }
+
+    fn flip_rows(&mut self) {
+        let row = (self.width as usize) * BYTES_PER_PIXEL;
+        let height = self.height as usize;
+        for y in 0..height / 2 {
+            let (top, bottom) = self.pixels.split_at_mut((height - 1 - y) * row);
+            top[y * row..(y + 1) * row].swap_with_slice(&mut bottom[..row]);
+        }
+    }
+
+    //copies `image` in with its top left corner at x, y, what sticks out is cut off
+    pub fn blit(&mut self, image: &PixelImage, x: u32, y: u32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let width = (image.width.min(self.width - x) as usize) * BYTES_PER_PIXEL;
+        for row in 0..image.height.min(self.height - y) as usize {
+            let from = row * (image.width as usize) * BYTES_PER_PIXEL;
+            let to = ((x as usize) + (row + (y as usize)) * (self.width as usize)) * BYTES_PER_PIXEL;
+            self.pixels[to..to + width].copy_from_slice(&image.pixels[from..from + width]);
+        }
+    }
+
+    pub fn encode_png(&self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&self.pixels).map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)?;
+        Ok(bytes)
+    }
+
+    //makes the directory the file goes in when it is not there
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        create_parent_dir(path)?;
+        std::fs::write(path, self.encode_png()?)
+    }
+}
+
+fn create_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => std::fs::create_dir_all(dir),
+        None => Ok(()),
+    }
+}
+
+//`dir/prefix-0001.png` with the first number no file has yet
+pub fn next_free_path(dir: impl AsRef<Path>, prefix: &str) -> PathBuf {
+    let dir = dir.as_ref();
+    (1..)
+        .map(|i| dir.join(format!("{}-{:04}.png", prefix, i)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+//a rectangle of chunks rendered at one level of detail into one image
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WorldExport {
+    //corners in chunk coordinates, both inside the rectangle
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    pub lod: u8,
+    //pixels across one chunk at that level of detail
+    pub chunk_pixels: u32,
+}
+
+impl WorldExport {
+    //the corners can be given in any order, the image has to stay below MAX_EXPORT_SIDE
+    pub fn new(
+        terrain: &Terrain,
+        settings: &RenderSettings,
+        lod: u8,
+        (x1, y1): (usize, usize),
+        (x2, y2): (usize, usize)
+    ) -> Result<Self, String> {
+        let (from, to) = ((x1.min(x2), y1.min(y2)), (x1.max(x2), y1.max(y2)));
+        if to.0 >= terrain.area_size() || to.1 >= terrain.area_size() {
+            return Err(
+                format!("chunk {} {} is outside of the world, which is {} chunks across", to.0, to.1, terrain.area_size())
+            );
+        }
+        if lod > 4 {
+            return Err(format!("the level of detail is {}, it has to be between 0 and 4", lod));
+        }
+        let export = WorldExport { from, to, lod, chunk_pixels: settings.chunk_texture_size >> lod };
+        let (width, height) = export.size();
+        if width > MAX_EXPORT_SIDE || height > MAX_EXPORT_SIDE {
+            return Err(
+                format!(
+                    "the image would be {}x{}, at most {} across fits, try a higher level of detail or fewer chunks",
+                    width,
+                    height,
+                    MAX_EXPORT_SIDE
+                )
+            );
+        }
+        Ok(export)
+    }
+
+    //every chunk of the world
+    pub fn whole(terrain: &Terrain, settings: &RenderSettings, lod: u8) -> Result<Self, String> {
+        let last = terrain.area_size() - 1;
+        WorldExport::new(terrain, settings, lod, (0, 0), (last, last))
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (
+            ((self.to.0 - self.from.0 + 1) as u32) * self.chunk_pixels,
+            ((self.to.1 - self.from.1 + 1) as u32) * self.chunk_pixels,
+        )
+    }
+
+    //row order
+    pub fn chunks(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.from.1..=self.to.1).flat_map(move |y| (self.from.0..=self.to.0).map(move |x| (x, y)))
+    }
+
+    //where a chunk goes in the image
+    pub fn offset(&self, (x, y): (usize, usize)) -> (u32, u32) {
+        (((x - self.from.0) as u32) * self.chunk_pixels, ((y - self.from.1) as u32) * self.chunk_pixels)
+    }
+}
+
+//writes an export to a png a row of chunks at a time, the chunks are added one by one in the
+//order next_chunk gives, so the rendering can be spread over frames and only one strip of the
+//image is ever in memory
+pub struct ExportWriter {
+    export: WorldExport,
+    path: PathBuf,
+    //straight to the file so every write reports its own error, the end of the png is written
+    //when it is dropped
+    png: png::StreamWriter<'static, File>,
+    strip: PixelImage,
+    added: usize,
+}
+
+impl ExportWriter {
+    //makes the directory and the file and writes the png header
+    pub fn create(export: WorldExport, path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        create_parent_dir(&path)?;
+        let (width, height) = export.size();
+        let mut encoder = png::Encoder::new(File::create(&path)?, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let png = encoder
+            .write_header()
+            .and_then(png::Writer::into_stream_writer)
+            .map_err(io::Error::other)?;
+        Ok(ExportWriter { export, path, png, strip: PixelImage::new(width, export.chunk_pixels), added: 0 })
+    }
+
+    pub fn export(&self) -> &WorldExport {
+        &self.export
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    //the chunk to add next, None once all of them are in
+    pub fn next_chunk(&self) -> Option<(usize, usize)> {
+        self.export.chunks().nth(self.added)
+    }
+
+    //chunks added and chunks in the export
+    pub fn progress(&self) -> (usize, usize) {
+        (self.added, self.export.chunks().count())
+    }
+
+    //the image of next_chunk, None leaves it transparent, a strip goes to the file once its last
+    //chunk is in
+    pub fn add_chunk(&mut self, image: Option<&PixelImage>) -> io::Result<()> {
+        let Some(coords) = self.next_chunk() else {
+            return Err(io::Error::other("every chunk of the export is already in"));
+        };
+        if let Some(image) = image {
+            let (x, _) = self.export.offset(coords);
+            self.strip.blit(image, x, 0);
+        }
+        self.added += 1;
+        if coords.0 == self.export.to.0 {
+            self.png.write_all(self.strip.pixels())?;
+            self.strip = PixelImage::new(self.strip.width(), self.strip.height());
+        }
+        Ok(())
+    }
+
+    //every chunk has to be in
+    pub fn finish(self) -> io::Result<PathBuf> {
+        if self.next_chunk().is_some() {
+            let (added, total) = self.progress();
+            return Err(io::Error::other(format!("only {} of {} chunks are in the export", added, total)));
+        }
+        self.png.finish().map_err(io::Error::other)?;
+        Ok(self.path)
+    }
+}
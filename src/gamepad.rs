@@ -0,0 +1,91 @@
+use crate::*;
+
+//how the sticks are read, kept in the bindings file next to the keys
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GamepadSettings {
+    //sticks are ignored until pushed further than this, 0..1
+    pub deadzone: f32,
+    pub aim_deadzone: f32,
+    //scales the left stick, a fully pushed stick walks at full speed from 1.0 up
+    pub sensitivity: f32,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        GamepadSettings {
+            deadzone: 0.15,
+            aim_deadzone: 0.3,
+            sensitivity: 1.0,
+        }
+    }
+}
+
+//raw stick values in -1..1 with y pointing up, like the pads report them
+#[derive(Copy, Clone, Default, Debug)]
+pub struct GamepadState {
+    pub left_stick: Vec2,
+    pub right_stick: Vec2,
+}
+
+//zero inside the deadzone, then rescaled so movement starts smoothly from its edge
+pub fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length().min(1.0);
+    if length <= deadzone {
+        return Vec2::new(0.0, 0.0);
+    }
+    stick.normalize() * ((length - deadzone) / (1.0 - deadzone))
+}
+
+impl GamepadSettings {
+    //the movement input, up on the stick is -y like W on the keyboard
+    pub fn movement(&self, state: &GamepadState) -> Vec2 {
+        let stick = (apply_deadzone(state.left_stick, self.deadzone) * self.sensitivity).clamp_length_max(
+            1.0
+        );
+        Vec2::new(stick.x, -stick.y)
+    }
+
+    //screen direction the right stick points to, None while it rests in the deadzone
+    pub fn aim(&self, state: &GamepadState) -> Option<Vec2> {
+        let stick = apply_deadzone(state.right_stick, self.aim_deadzone);
+        if stick.length() == 0.0 {
+            return None;
+        }
+        Some(Vec2::new(stick.x, -stick.y).normalize())
+    }
+}
+
+//reads the most recently used pad through gilrs
+#[cfg(feature = "gamepad")]
+pub struct Gamepads {
+    gilrs: gilrs::Gilrs,
+    active: Option<gilrs::GamepadId>,
+}
+
+#[cfg(feature = "gamepad")]
+impl Gamepads {
+    pub fn new() -> Result<Self, String> {
+        let gilrs = gilrs::Gilrs::new().map_err(|e| e.to_string())?;
+        Ok(Gamepads { gilrs, active: None })
+    }
+
+    //call once per frame, None when no pad is connected
+    pub fn poll(&mut self) -> Option<GamepadState> {
+        while let Some(event) = self.gilrs.next_event() {
+            self.active = Some(event.id);
+        }
+        let gamepad = match self.active {
+            Some(id) if self.gilrs.connected_gamepad(id).is_some() => self.gilrs.gamepad(id),
+            _ => {
+                let (id, gamepad) = self.gilrs.gamepads().next()?;
+                self.active = Some(id);
+                gamepad
+            }
+        };
+        use gilrs::Axis;
+        Some(GamepadState {
+            left_stick: Vec2::new(gamepad.value(Axis::LeftStickX), gamepad.value(Axis::LeftStickY)),
+            right_stick: Vec2::new(gamepad.value(Axis::RightStickX), gamepad.value(Axis::RightStickY)),
+        })
+    }
+}
@@ -0,0 +1,63 @@
+#![cfg_attr(debug_assertions, allow(warnings))]
+
+use notan::draw::*;
+use notan::math::*;
+use notan::prelude::*;
+
+pub mod render_utilities;
+pub mod angle;
+pub mod transform;
+pub mod player;
+pub mod chunk;
+pub mod pathfinding;
+pub mod npc;
+pub mod ecs;
+pub mod systems;
+pub mod spatial;
+pub mod net;
+pub mod server;
+pub mod timestep;
+pub mod terrain;
+pub mod sim;
+pub mod replay;
+pub mod bindings;
+pub mod rebind;
+pub mod gamepad;
+pub mod animation;
+pub mod profile;
+pub mod config;
+pub mod cli;
+pub mod assets;
+pub mod scene;
+pub mod ui;
+pub mod debug;
+pub mod console;
+pub mod export;
+pub use render_utilities::*;
+pub use angle::*;
+pub use transform::*;
+pub use player::*;
+pub use chunk::*;
+pub use npc::*;
+pub use ecs::*;
+pub use systems::*;
+pub use spatial::*;
+pub use timestep::*;
+pub use terrain::*;
+pub use sim::*;
+pub use replay::*;
+pub use bindings::*;
+pub use rebind::*;
+pub use gamepad::*;
+pub use animation::*;
+pub use profile::*;
+pub use config::*;
+pub use assets::*;
+pub use scene::*;
+pub use ui::*;
+pub use debug::*;
+pub use console::*;
+pub use export::*;
+
+//default width and height of the world in chunks, indifi.toml can change it
+pub const AREA_SIZE: usize = 16;
@@ -1,137 +1,1266 @@
 #![cfg_attr(debug_assertions, allow(warnings))]
 
+use indifi::*;
+use indifi::cli::{ self, Command, PlayArgs, WorldArgs };
+use indifi::net::NetClient;
+use std::path::PathBuf;
 use notan::draw::*;
 use notan::prelude::*;
-use std::collections::HashMap;
 
-mod render_utilities;
-mod transform;
-mod player;
-mod chunk;
-use render_utilities::*;
-use transform::*;
-use player::*;
-use chunk::*;
-
-const AREA_SIZE: usize = 16;
+//milliseconds of loading work done per frame, the rest of the frame draws the progress
+const LOADING_BUDGET_MS: u64 = 12;
+//where the editor saves a world that was not loaded from a file
+const DEFAULT_EDITED_WORLD: &str = "edited.world";
+//where screenshots and world exports go unless a path is given
+const SCREENSHOT_DIR: &str = "screenshots";
+const MENU_WIDTH: f32 = 260.0;
+const SETTINGS_WIDTH: f32 = 380.0;
 
 #[derive(AppState)]
 struct State {
+    screens: SceneStack<Screen>,
+    session: Session,
+}
+
+//what every screen can reach
+struct Session {
+    args: PlayArgs,
+    config: Config,
+    //the menus and the loading screen only use what is built in
+    background: Texture,
+    font: Font,
+    //there while a game is being played, paused or edited
+    game: Option<Box<Game>>,
+    //characters typed since the last draw, for the text fields
+    typed: String,
+    //kept between games so the history stays
+    console: Console,
+    commands: CommandRegistry<Game>,
+    //why the last game could not start, shown on the main menu
+    menu_error: Option<String>,
+}
+
+enum Screen {
+    MainMenu(UiState),
+    Loading(Box<Loading>),
+    Playing,
+    Paused(UiState),
+    Settings(Box<Settings>),
+    Bindings(RebindScreen),
+    Editor(Editor),
+    Console,
+}
+
+impl Scene for Screen {
+    type Context = Session;
+
+    fn enter(&mut self, session: &mut Session) {
+        match self {
+            Screen::Bindings(rebind) => rebind.open(),
+            Screen::Editor(_) => {
+                if let Some(game) = &mut session.game {
+                    game.camera = Some(game.sim.player_render_pos());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn exit(&mut self, session: &mut Session) {
+        match self {
+            Screen::Playing => {
+                if let Some(game) = session.game.take() {
+                    end_game(*game);
+                }
+            }
+            Screen::Bindings(_) => {
+                if let Some(game) = &session.game {
+                    if let Err(e) = game.bindings.save(&game.bindings_path) {
+                        eprintln!("could not save key bindings {}: {}", game.bindings_path, e);
+                    }
+                }
+            }
+            Screen::Editor(_) => {
+                if let Some(game) = &mut session.game {
+                    game.camera = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        matches!(self, Screen::Paused(_) | Screen::Settings(_) | Screen::Bindings(_) | Screen::Console)
+    }
+}
+
+//an editable copy of the settings, written to the settings file on save
+struct Settings {
+    ui: UiState,
+    config: Config,
+    name: String,
+    width: String,
+    height: String,
+    zoom: f32,
+    area_size: f32,
+    //why the last save did not work
+    error: Option<String>,
+}
+
+impl Settings {
+    fn new(session: &Session) -> Self {
+        let config = session.config.clone();
+        Settings {
+            ui: UiState::default().with_keyboard(),
+            name: session.args.name.clone().unwrap_or_default(),
+            width: config.window.width.to_string(),
+            height: config.window.height.to_string(),
+            zoom: config.render.zoom as f32,
+            area_size: config.world.area_size as f32,
+            config,
+            error: None,
+        }
+    }
+}
+
+//paints walls on the world while the simulation waits, only offline since the server
+//keeps its own terrain
+struct Editor {
+    //where Action::Save writes the world
+    path: String,
+    //false until the buttons are let go, so the click that opened the editor builds nothing
+    painting: bool,
+}
+
+//the art the game is drawn with
+struct LoadedAssets {
+    font: Font,
+    atlas: Texture,
+    player_sheet: Texture,
+}
+
+//everything plan_game decides from the options, the profile and the settings
+struct GameSetup {
+    simulation: SimulationBuilder,
+    //gets its sprite once the sheet is loaded
+    player: PlayerBuilder,
+    player_sprite: SpriteSheet,
+    profile: Profile,
+    profiles_dir: String,
+    keep_position: bool,
+    bindings: KeyBindings,
+    bindings_path: String,
+    replay: Option<Replay>,
+    record: Option<String>,
+    connect: Option<String>,
+    config: Config,
+    config_path: String,
+    overrides: Vec<(String, String)>,
+    assets: AssetDir,
+    world_path: String,
+}
+
+enum LoadingStage {
+    //notan reads the files, None without an assets directory
+    Assets {
+        list: Option<AssetList>,
+        setup: GameSetup,
+    },
+    Terrain {
+        generator: TerrainGenerator,
+        setup: GameSetup,
+        loaded: LoadedAssets,
+    },
+    //the low-res chunk textures are baked into the game until it has all of them
+    Chunks(Box<Game>),
+}
+
+struct Loading {
+    //None only while one stage turns into the next
+    stage: Option<LoadingStage>,
+}
+
+struct Game {
     font: Font,
-    fps: String,
-    player: Player,
+    //milliseconds the last update took
+    update_ms: f32,
+    frame_times: FrameTimes,
+    sim: Simulation,
+    net: Option<NetClient>,
+    //`--record` keeps every tick's input here and writes it to the path on exit
+    recording: Option<(Replay, String)>,
+    //`--replay` feeds these inputs to the player until they run out
+    replay: Option<Replay>,
+    replay_tick: usize,
+    bindings: KeyBindings,
+    bindings_path: String,
+    profile: Profile,
+    profiles_dir: String,
+    //only offline sessions remember where the player was, the others start where they have to
+    keep_position: bool,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<Gamepads>,
+    //the right stick aimed last, the mouse takes over again once it moves
+    gamepad_aim: Option<Vec2>,
     mouse_pos: Vec2,
+    mouse_world_pos: Vec2,
     chunks: Vec<Chunk>,
-    chunk_i: usize,
+    //the chunk textures are stale and get rendered again on the next draw
+    terrain_changed: bool,
+    //chunks the editor changed, baked again on the next draw
+    stale_chunks: Vec<(usize, usize)>,
+    //the editor looks around here instead of following the player
+    camera: Option<Vec2>,
+    world_path: String,
+    config: Config,
+    //indifi.toml and the --set options it gets reloaded with
+    config_file: WatchedFile,
+    overrides: Vec<(String, String)>,
+    assets: AssetDir,
+    //the debug panel, it only takes the mouse
+    ui: UiState,
+    //seconds since the watched files were checked
+    since_watch: f32,
+    //assets that changed on disk, loaded again on the next draw
+    stale_assets: Vec<AssetFile>,
     textures: Vec<Texture>,
     render_size_pow: u8,
+    //chunk outlines instead of the ground
     debug: bool,
-}
-
-impl State {
-    fn get_chunk(&mut self, index: usize) -> Option<&mut Chunk> {
-        self.chunks.get_mut(index)
-    }
+    //the debug overlay in the corner
+    overlay: bool,
+    //the level of detail of every chunk, set from the console
+    forced_lod: Option<u8>,
+    //the next draw is also written to a file
+    screenshot: bool,
+    //started by the console, a chunk of it is rendered every draw since that needs the graphics
+    export: Option<ExportWriter>,
 }
 
 #[notan_main]
 fn main() {
-    let window_config = WindowConfig::new().set_high_dpi(true);
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(Command::Play(args)) => args,
+        Ok(Command::Generate { path, world }) => {
+            exit_on_error(generate(&path, &world));
+            return;
+        }
+        Ok(Command::Stats(world)) => {
+            exit_on_error(stats(&world));
+            return;
+        }
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let config = load_config(&args.config, &args.overrides).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let window_config = WindowConfig::new()
+        .set_size(config.window.width, config.window.height)
+        .set_fullscreen(config.window.fullscreen)
+        .set_vsync(config.window.vsync)
+        .set_high_dpi(config.window.high_dpi);
+    let [texture_loader, font_loader] = asset_loaders();
     notan
-        ::init_with(setup)
+        ::init_with(move |gfx: &mut Graphics| setup(gfx, args, config))
         .add_config(window_config)
         .add_config(DrawConfig)
+        .add_loader(texture_loader)
+        .add_loader(font_loader)
         .update(update)
         .draw(draw)
+        .event(event)
         .build()
         .unwrap();
 }
 
-fn setup(gfx: &mut Graphics) -> State {
-    let font = gfx.create_font(include_bytes!("assets/Ubuntu-B.ttf")).unwrap();
-    let mut textures = Vec::<Texture>::new();
-    textures.push(
-        gfx
-            .create_texture()
-            .from_image(include_bytes!("assets/atlas_test_bigger.png"))
-            .build()
-            .unwrap()
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+//the config file with the overrides on top, every error mentions where it came from
+fn load_config(path: &str, overrides: &[(String, String)]) -> Result<Config, String> {
+    let mut config = Config::load(path).map_err(|e| format!("{}: {}", path, e))?;
+    for (key, value) in overrides {
+        config.set(key, value).map_err(|e| format!("--set {}={}: {}", key, value, e))?;
+    }
+    config.validate().map_err(|e| format!("invalid settings in {} or the options:\n{}", path, e))?;
+    Ok(config)
+}
+
+//the world generate and stats work on, read from a file or generated from the seed
+fn world_terrain(args: &WorldArgs) -> Result<Terrain, String> {
+    if let Some(path) = &args.world {
+        return Terrain::load(path).map_err(|e| format!("{}: {}", path, e));
+    }
+    //only the size matters here, the rest of the settings is not checked
+    let area_size = match args.area_size {
+        Some(area_size) => area_size,
+        None => Config::load(&args.config).map_err(|e| format!("{}: {}", args.config, e))?.world.area_size,
+    };
+    if !(1..=MAX_AREA_SIZE).contains(&area_size) {
+        return Err(format!("the area size is {}, it has to be between 1 and {}", area_size, MAX_AREA_SIZE));
+    }
+    let seed = args.seed.unwrap_or_else(notan::random::rand::random);
+    Ok(Terrain::generate_sized(seed, area_size))
+}
+
+fn generate(path: &str, args: &WorldArgs) -> Result<(), String> {
+    let terrain = world_terrain(args)?;
+    terrain.save(path).map_err(|e| format!("could not write {}: {}", path, e))?;
+    println!("wrote world {} ({}x{} chunks) to {}", terrain.seed(), terrain.area_size(), terrain.area_size(), path);
+    Ok(())
+}
+
+fn stats(args: &WorldArgs) -> Result<(), String> {
+    let terrain = world_terrain(args)?;
+    let stats = terrain.stats();
+    println!(
+        "world {}, {}x{} chunks, {}x{} tiles",
+        terrain.seed(),
+        terrain.area_size(),
+        terrain.area_size(),
+        terrain.size_in_tiles(),
+        terrain.size_in_tiles()
     );
+    let percent = |count: usize| ((count as f32) * 100.0) / (stats.tiles as f32);
+    for (name, count) in [("walls", stats.walls), ("floor", stats.floor), ("ice", stats.ice), ("mud", stats.mud)] {
+        println!("{:<6}{:>8} {:>6.1}%", name, count, percent(count));
+    }
+    Ok(())
+}
+
+fn setup(gfx: &mut Graphics, args: PlayArgs, config: Config) -> State {
+    let embedded = AssetDir::embedded();
+    let mut session = Session {
+        args,
+        config,
+        background: embedded.texture(gfx, AssetFile::Atlas),
+        font: embedded.font(gfx),
+        game: None,
+        typed: String::new(),
+        console: Console::default(),
+        commands: game_commands(),
+        menu_error: None,
+    };
+    let mut screens = SceneStack::new();
+    screens.push(Screen::MainMenu(UiState::default().with_keyboard()), &mut session);
+    State { screens, session }
+}
+
+//starts reading the assets of a new game
+fn start_loading(assets: &mut Assets, session: &Session) -> Result<Loading, String> {
+    let asset_dir = match &session.args.assets {
+        Some(dir) => AssetDir::from_dir(dir),
+        None => AssetDir::embedded(),
+    };
+    let list = match asset_dir.dir() {
+        Some(_) => Some(asset_dir.load_list(assets).map_err(|e| format!("could not load the assets: {}", e))?),
+        None => None,
+    };
+    let setup = plan_game(session.args.clone(), session.config.clone(), asset_dir);
+    Ok(Loading { stage: Some(LoadingStage::Assets { list, setup }) })
+}
 
-    let mut chunks = Vec::new();
-    for y in 0..AREA_SIZE {
-        for x in 0..AREA_SIZE {
-            let mut chunk = Chunk::new(gfx, x, y);
-            chunk.render_low_res(gfx, &textures);
-            chunks.push(chunk);
+//everything about the session that is decided before the assets and the terrain are there
+fn plan_game(args: PlayArgs, config: Config, assets: AssetDir) -> GameSetup {
+    let PlayArgs {
+        connect,
+        name,
+        profiles: profiles_dir,
+        tick_rate,
+        record,
+        replay,
+        bindings: bindings_path,
+        facing,
+        seed,
+        world,
+        position,
+        config: config_path,
+        overrides,
+        ..
+    } = args;
+    let mut profile = Profile::choose(&profiles_dir, name.as_deref()).unwrap_or_else(|e| {
+        eprintln!("could not load profile: {}", e);
+        Profile::new(name.as_deref().unwrap_or("Blank"))
+    });
+    if let Some(facing) = facing {
+        profile.facing = facing;
+    }
+    let player_sprite = SpriteSheet { facing: profile.facing, ..PLAYER_SHEET };
+    let bindings = KeyBindings::load(&bindings_path).unwrap_or_else(|e| {
+        eprintln!("could not load key bindings {}: {}", bindings_path, e);
+        KeyBindings::default()
+    });
+    let replay = replay.and_then(|path| {
+        match Replay::load(&path) {
+            Ok(replay) => Some(replay),
+            Err(e) => {
+                eprintln!("could not load replay {}: {}", path, e);
+                None
+            }
+        }
+    });
+    //the editor saves over the world it was started on
+    let world_path = world.clone().unwrap_or_else(|| DEFAULT_EDITED_WORLD.to_string());
+    let terrain = world.and_then(|path| {
+        match Terrain::load(&path) {
+            Ok(terrain) => Some(terrain),
+            Err(e) => {
+                eprintln!("could not load world {}: {}", path, e);
+                None
+            }
         }
+    });
+    //replays have to start where they were recorded and servers place the player themselves
+    let keep_position = replay.is_none() && connect.is_none();
+    let area_size = match (&replay, &terrain) {
+        (Some(replay), _) => replay.area_size,
+        (None, Some(terrain)) => terrain.area_size(),
+        (None, None) => config.world.area_size,
+    };
+    let simulation = match (&replay, terrain) {
+        (Some(replay), _) => replay.simulation(),
+        //a networked client has to step at the rate the server expects its inputs
+        _ if connect.is_some() =>
+            SimulationBuilder::new(notan::random::rand::random()).tick_rate(net::TICK_RATE),
+        (None, Some(terrain)) => SimulationBuilder::new(0).terrain(terrain).tick_rate(tick_rate),
+        (None, None) => {
+            let saved = profile.seed.filter(|_| keep_position);
+            SimulationBuilder::new(seed.or(saved).unwrap_or_else(notan::random::rand::random))
+                .tick_rate(tick_rate)
+                .area_size(config.world.area_size)
+        }
+    };
+    //the saved position only fits the world it was saved in
+    let saved = profile.position.filter(|_| keep_position && profile.seed == Some(simulation.seed()));
+    let position = position.filter(|position| {
+        let inside = Chunk::in_world(*position, area_size);
+        if !inside {
+            eprintln!(
+                "--position {},{} is outside of the world, which is {} across",
+                position.x,
+                position.y,
+                area_size * Chunk::size()
+            );
+        }
+        inside
+    });
+    let start = match (&replay, position.or(saved)) {
+        (Some(replay), _) => replay.start,
+        (None, Some(start)) => start,
+        (None, None) => config.world.player_start,
+    };
+    let player = PlayerBuilder::new()
+        .display_name(&profile.name)
+        .color(profile.color)
+        .movement_model(bindings.movement_model)
+        .position(start.x, start.y);
+    //the server's world has no npcs, local ones would push the predicted player around and the
+    //server would snap it back every time
+    let simulation = match connect {
+        Some(_) => simulation,
+        None => demo_npcs(simulation),
+    };
+    GameSetup {
+        simulation,
+        player,
+        player_sprite,
+        profile,
+        profiles_dir,
+        keep_position,
+        bindings,
+        bindings_path,
+        replay,
+        record,
+        connect,
+        config,
+        config_path,
+        overrides,
+        assets,
+        world_path,
     }
+}
+
+//a few npcs around the middle of the default world, one of each behaviour
+fn demo_npcs(simulation: SimulationBuilder) -> SimulationBuilder {
+    simulation
+        .npc(NpcBuilder::new(Behaviour::wander(Vec2::new(2000.0, 2000.0))).position(2000.0, 2000.0))
+        .npc(NpcBuilder::new(Behaviour::wander(Vec2::new(2150.0, 1950.0))).position(2150.0, 1950.0))
+        .npc(
+            NpcBuilder::new(
+                Behaviour::patrol(
+                    vec![Vec2::new(1900.0, 2100.0), Vec2::new(2000.0, 2200.0), Vec2::new(2100.0, 2100.0)]
+                )
+            )
+                .position(1900.0, 2100.0)
+                .color(Color::YELLOW)
+        )
+        .npc(
+            NpcBuilder::new(Behaviour::Follow { distance: 24.0 })
+                .position(2100.0, 2048.0)
+                .color(Color::ORANGE)
+        )
+        .npc(
+            NpcBuilder::new(Behaviour::Flee { distance: 64.0 })
+                .position(2048.0, 2120.0)
+                .color(Color::RED)
+        )
+}
 
-    State {
+//the game once the assets are loaded and the terrain generated, the chunks are baked afterwards
+fn start_game(setup: GameSetup, loaded: LoadedAssets, terrain: Option<Terrain>) -> Game {
+    let GameSetup {
+        simulation,
+        player,
+        player_sprite,
+        profile,
+        profiles_dir,
+        keep_position,
+        bindings,
+        bindings_path,
+        replay,
+        record,
+        connect,
+        config,
+        config_path,
+        overrides,
+        assets,
+        world_path,
+    } = setup;
+    let LoadedAssets { font, atlas, player_sheet } = loaded;
+    let simulation = match terrain {
+        Some(terrain) => simulation.terrain(terrain),
+        None => simulation,
+    };
+    let sim = simulation.player(player.sprite(player_sheet.clone(), player_sprite)).build();
+    let recording = record.map(|path| (Replay::new(&sim), path));
+
+    let net = connect.and_then(|addr| {
+        let color = sim.world.renderables
+            .get(sim.player())
+            .and_then(|renderable| renderable.color())
+            .unwrap_or(Color::BLUE);
+        match NetClient::connect(&addr, &profile.name, color) {
+            Ok(mut client) => {
+                client.set_remote_sprite(player_sheet.clone(), player_sprite);
+                Some(client)
+            }
+            Err(e) => {
+                eprintln!("could not connect to {}: {}", addr, e);
+                None
+            }
+        }
+    });
+
+    Game {
         font,
-        fps: "¯\\_(ツ)_/¯".to_string(),
-        player: PlayerBuilder::new().color_random().build(),
+        update_ms: 0.0,
+        frame_times: FrameTimes::default(),
+        sim,
+        net,
+        recording,
+        replay,
+        replay_tick: 0,
+        bindings,
+        bindings_path,
+        render_size_pow: config.clamp_zoom(profile.zoom.unwrap_or(config.render.zoom)),
+        profile,
+        profiles_dir,
+        keep_position,
+        #[cfg(feature = "gamepad")]
+        gamepads: Gamepads::new()
+            .map_err(|e| eprintln!("gamepads are not available: {}", e))
+            .ok(),
+        gamepad_aim: None,
         mouse_pos: Vec2::new(0.0, 0.0),
-        chunks,
-        textures,
-        render_size_pow: 8,
-        chunk_i: 0,
+        mouse_world_pos: Vec2::new(0.0, 0.0),
+        chunks: Vec::new(),
+        terrain_changed: false,
+        stale_chunks: Vec::new(),
+        camera: None,
+        world_path,
+        config,
+        config_file: WatchedFile::new(config_path),
+        overrides,
+        assets,
+        ui: UiState::default(),
+        since_watch: 0.0,
+        stale_assets: Vec::new(),
+        textures: vec![atlas],
         debug: false,
+        overlay: true,
+        forced_lod: None,
+        screenshot: false,
+        export: None,
     }
 }
 
+//whatever notan has not read yet, or the embedded copies without an assets directory
+fn take_assets(gfx: &mut Graphics, dir: &AssetDir, list: Option<AssetList>) -> LoadedAssets {
+    match list {
+        Some(mut list) =>
+            LoadedAssets {
+                font: dir.take_font(gfx, &mut list),
+                atlas: dir.take_texture(gfx, &mut list, AssetFile::Atlas),
+                player_sheet: dir.take_texture(gfx, &mut list, AssetFile::PlayerSheet),
+            },
+        None =>
+            LoadedAssets {
+                font: dir.font(gfx),
+                atlas: dir.texture(gfx, AssetFile::Atlas),
+                player_sheet: dir.texture(gfx, AssetFile::PlayerSheet),
+            },
+    }
+}
+
+//moves the loading on for one frame's worth of time, the game once every chunk is baked
+fn advance_loading(app: &App, gfx: &mut Graphics, loading: &mut Loading) -> Option<Game> {
+    let started = app.date_now();
+    let in_budget = || app.date_now() - started < LOADING_BUDGET_MS;
+    let stage = loading.stage.take()?;
+    loading.stage = Some(match stage {
+        LoadingStage::Assets { list, setup } if list.as_ref().is_none_or(|list| list.is_loaded()) => {
+            let loaded = take_assets(gfx, &setup.assets, list);
+            match setup.simulation.terrain_generator() {
+                Some(generator) => LoadingStage::Terrain { generator, setup, loaded },
+                None => LoadingStage::Chunks(Box::new(start_game(setup, loaded, None))),
+            }
+        }
+        LoadingStage::Terrain { mut generator, setup, loaded } => {
+            while generator.step() && in_budget() {}
+            if generator.is_done() {
+                LoadingStage::Chunks(Box::new(start_game(setup, loaded, Some(generator.finish()))))
+            } else {
+                LoadingStage::Terrain { generator, setup, loaded }
+            }
+        }
+        LoadingStage::Chunks(mut game) => {
+            let area_size = game.sim.terrain.area_size();
+            while game.chunks.len() < area_size * area_size && in_budget() {
+                let index = game.chunks.len();
+                let chunk = create_chunk(gfx, &game.textures, &game.sim.terrain, &game.config.render, index);
+                game.chunks.push(chunk);
+            }
+            if game.chunks.len() == area_size * area_size {
+                return Some(*game);
+            }
+            LoadingStage::Chunks(game)
+        }
+        stage => stage,
+    });
+    None
+}
+
+fn draw_loading(gfx: &mut Graphics, loading: &Loading, session: &Session) {
+    let (label, progress) = match &loading.stage {
+        Some(LoadingStage::Assets { list, .. }) =>
+            ("loading assets", list.as_ref().map_or(1.0, |list| list.progress())),
+        Some(LoadingStage::Terrain { generator, .. }) => ("generating the world", generator.progress()),
+        Some(LoadingStage::Chunks(game)) => {
+            let area_size = game.sim.terrain.area_size();
+            ("drawing the ground", (game.chunks.len() as f32) / ((area_size * area_size) as f32))
+        }
+        None => ("", 1.0),
+    };
+    render_loading(gfx, &session.background, &session.font, label, progress);
+}
+
+fn event(state: &mut State, event: Event) {
+    match event {
+        //the screens save what they have to on the way out
+        Event::Exit => state.screens.clear(&mut state.session),
+        Event::ReceivedCharacter(c) => state.session.typed.push(c),
+        _ => {}
+    }
+}
+
+//saves the profile and the recording and leaves the server
+fn end_game(mut state: Game) {
+    if let Some(net) = &mut state.net {
+        let _ = net.disconnect();
+    }
+    if let Some((replay, path)) = &state.recording {
+        if let Err(e) = replay.save(path) {
+            eprintln!("could not save replay {}: {}", path, e);
+        }
+    }
+    state.profile.zoom = Some(state.render_size_pow);
+    if state.keep_position {
+        state.profile.seed = Some(state.sim.seed());
+        state.profile.position = Some(state.sim.player_pos());
+    }
+    if let Err(e) = state.profile.save(&state.profiles_dir) {
+        eprintln!("could not save profile {}: {}", state.profile.name, e);
+    }
+}
+
+//only the top screen gets the input, the menus handle theirs while they are drawn
 fn update(app: &mut App, state: &mut State) {
-    let time = app.date_now();
+    let State { screens, session } = state;
+    let transition = match screens.top_mut() {
+        Some(Screen::Playing) =>
+            match &mut session.game {
+                Some(game) => update_playing(app, game),
+                None => Transition::Stay,
+            }
+        Some(Screen::Paused(_)) =>
+            match &mut session.game {
+                Some(game) => update_paused(app, game),
+                None => Transition::Pop,
+            }
+        Some(Screen::Settings(_)) => {
+            if let Some(game) = session.game.as_mut().filter(|game| game.net.is_some()) {
+                update_game(app, game, &Input::default());
+            }
+            Transition::Stay
+        }
+        Some(Screen::Bindings(rebind)) =>
+            match &mut session.game {
+                Some(game) => update_bindings(app, game, rebind),
+                None => Transition::Pop,
+            }
+        Some(Screen::Editor(editor)) =>
+            match &mut session.game {
+                Some(game) => update_editor(app, game, editor),
+                None => Transition::Pop,
+            }
+        Some(Screen::Console) =>
+            match &mut session.game {
+                Some(game) => update_console(app, game),
+                None => Transition::Pop,
+            }
+        Some(Screen::MainMenu(_)) | Some(Screen::Loading(_)) | None => Transition::Stay,
+    };
+    //the key that opened the console is not typed into it
+    if matches!(transition, Transition::Push(Screen::Console)) {
+        session.typed.clear();
+    }
+    screens.apply(transition, session);
+}
+
+fn update_playing(app: &mut App, state: &mut Game) -> Transition<Screen> {
+    let keyboard = &app.keyboard;
+    if state.bindings.was_pressed(keyboard, Action::Pause) {
+        return Transition::Push(Screen::Paused(UiState::default().with_keyboard()));
+    }
+    if state.bindings.was_pressed(keyboard, Action::OpenBindings) {
+        return Transition::Push(Screen::Bindings(RebindScreen::default()));
+    }
+    if state.bindings.was_pressed(keyboard, Action::OpenEditor) && state.net.is_none() && state.recording.is_none() {
+        return Transition::Push(Screen::Editor(editor(state)));
+    }
+    if state.bindings.was_pressed(keyboard, Action::ToggleConsole) {
+        return Transition::Push(Screen::Console);
+    }
+    view_keys(app, state);
+    let input = read_input(app, state);
+    update_game(app, state, &input);
+    Transition::Stay
+}
+
+//the simulation only waits offline, a server keeps going without us
+fn update_paused(app: &mut App, state: &mut Game) -> Transition<Screen> {
+    if state.net.is_some() {
+        update_game(app, state, &Input::default());
+    }
+    if state.bindings.was_pressed(&app.keyboard, Action::Pause) { Transition::Pop } else { Transition::Stay }
+}
+
+//waits like the pause menu, online the player stands still while the keyboard is taken
+fn update_bindings(app: &mut App, state: &mut Game, rebind: &mut RebindScreen) -> Transition<Screen> {
+    let closed = rebind.update(app, &mut state.bindings);
+    if let Some(input) = state.sim.world.inputs.get_mut(state.sim.player()) {
+        input.model = state.bindings.movement_model;
+    }
+    if state.net.is_some() {
+        update_game(app, state, &Input::default());
+    }
+    if closed { Transition::Pop } else { Transition::Stay }
+}
+
+//the world goes on while the console is open and the player stands still, over the editor it
+//waits like the editor does
+fn update_console(app: &mut App, state: &mut Game) -> Transition<Screen> {
+    let keyboard = &app.keyboard;
+    if state.bindings.was_pressed(keyboard, Action::ToggleConsole) || state.bindings.was_pressed(keyboard, Action::Pause) {
+        return Transition::Pop;
+    }
+    if state.camera.is_none() || state.net.is_some() {
+        update_game(app, state, &Input::default());
+    }
+    Transition::Stay
+}
+
+//a replay only holds the player's inputs, anything else changing the world would play back
+//differently than it was recorded
+fn not_recording(state: &Game) -> Result<(), String> {
+    match state.recording {
+        Some(_) => Err("not while recording a replay".to_string()),
+        None => Ok(()),
+    }
+}
+
+fn game_commands() -> CommandRegistry<Game> {
+    CommandRegistry::<Game>::new()
+        .command("tp", "<x> <y>", "puts the player or the editor camera at a world position", |state, args| {
+            let pos = Vec2::new(parse_arg(args, 0, "x")?, parse_arg(args, 1, "y")?);
+            match &mut state.camera {
+                Some(camera) => *camera = pos,
+                None if state.net.is_some() => {
+                    return Err("the server decides where the player is".to_string());
+                }
+                None => {
+                    not_recording(state)?;
+                    state.sim.teleport_player(pos);
+                }
+            }
+            Ok(format!("moved to {} {}", pos.x, pos.y))
+        })
+        .command("seed", "", "prints the seed of the world", |state, _| Ok(state.sim.seed().to_string()))
+        .command("regen", "chunk <x> <y>", "puts back the generated tiles of a chunk", |state, args| {
+            if state.net.is_some() {
+                return Err("the server decides the world".to_string());
+            }
+            not_recording(state)?;
+            if args.first().map(String::as_str) != Some("chunk") {
+                return Err("usage: regen chunk <x> <y>".to_string());
+            }
+            let coords = (parse_arg(&args[1..], 0, "x")?, parse_arg(&args[1..], 1, "y")?);
+            if !state.sim.terrain.regenerate_chunk(coords) {
+                return Err(format!("chunk {} {} is outside of the world", coords.0, coords.1));
+            }
+            state.stale_chunks.push(coords);
+            Ok(format!("regenerated chunk {} {}", coords.0, coords.1))
+        })
+        .command("setTile", "<x> <y> <id>", "puts an atlas tile at a tile position, above 16 are walls", |state, args| {
+            if state.net.is_some() {
+                return Err("the server decides the world".to_string());
+            }
+            not_recording(state)?;
+            let tile = (parse_arg(args, 0, "x")?, parse_arg(args, 1, "y")?);
+            let id = parse_arg(args, 2, "id")?;
+            let coords = state.sim.terrain
+                .set_tile(tile, id)
+                .ok_or_else(|| format!("tile {} {} is outside of the world", tile.0, tile.1))?;
+            state.stale_chunks.push(coords);
+            Ok(format!("tile {} {} is {}", tile.0, tile.1, id))
+        })
+        .command("save", "[path]", "writes the world, where it was loaded from by default", |state, args| {
+            let path = args.first().unwrap_or(&state.world_path);
+            state.sim.terrain.save(path).map_err(|e| format!("could not write {}: {}", path, e))?;
+            Ok(format!("wrote world {}", path))
+        })
+        .command("load", "[path]", "replaces the world with one from a file", |state, args| {
+            if state.net.is_some() {
+                return Err("the server decides the world".to_string());
+            }
+            not_recording(state)?;
+            let path = args.first().unwrap_or(&state.world_path).clone();
+            let terrain = Terrain::load(&path).map_err(|e| format!("{}: {}", path, e))?;
+            state.sim.set_terrain(terrain);
+            state.terrain_changed = true;
+            state.world_path = path;
+            Ok(format!("loaded world {}", state.world_path))
+        })
+        .command("screenshot", "", "writes the next frame to a png", |state, _| {
+            state.screenshot = true;
+            Ok(format!("the next frame goes to {}", SCREENSHOT_DIR))
+        })
+        .command(
+            "export",
+            "<lod> [<x1> <y1> <x2> <y2>] [path]",
+            "renders the whole world or a rectangle of chunks into one png, lod 0 is full detail",
+            |state, args| {
+                let lod = parse_arg(args, 0, "lod")?;
+                let terrain = &state.sim.terrain;
+                let (export, rest) = if args.len() >= 5 {
+                    let from = (parse_arg(args, 1, "x1")?, parse_arg(args, 2, "y1")?);
+                    let to = (parse_arg(args, 3, "x2")?, parse_arg(args, 4, "y2")?);
+                    (WorldExport::new(terrain, &state.config.render, lod, from, to)?, &args[5..])
+                } else {
+                    (WorldExport::whole(terrain, &state.config.render, lod)?, &args[1..])
+                };
+                let path = match rest {
+                    [] => next_free_path(SCREENSHOT_DIR, "world"),
+                    [path] => PathBuf::from(path),
+                    _ => return Err("usage: export <lod> [<x1> <y1> <x2> <y2>] [path]".to_string()),
+                };
+                if state.export.is_some() {
+                    return Err("an export is already running".to_string());
+                }
+                let (width, height) = export.size();
+                let writer = ExportWriter::create(export, &path).map_err(|e|
+                    format!("could not write {}: {}", path.display(), e)
+                )?;
+                state.export = Some(writer);
+                Ok(format!("exporting {}x{} pixels to {}, a chunk a frame", width, height, path.display()))
+            }
+        )
+        .variable(
+            "zoom",
+            "the screen is 2^zoom world units wide",
+            |state| state.render_size_pow.to_string(),
+            |state, value| {
+                state.render_size_pow = state.config.clamp_zoom(value.parse().map_err(|_| "not a number")?);
+                Ok(())
+            }
+        )
+        .variable(
+            "lod",
+            "level of detail of every chunk, 0 to 4, or auto",
+            |state| state.forced_lod.map_or("auto".to_string(), |lod| lod.to_string()),
+            |state, value| {
+                state.forced_lod = match value {
+                    "auto" => None,
+                    _ => Some(value.parse::<u8>().map_err(|_| "not a number or auto")?.min(4)),
+                };
+                update_lods(state);
+                Ok(())
+            }
+        )
+        .variable(
+            "tick_rate",
+            "simulation ticks per second",
+            |state| state.sim.tick_rate().to_string(),
+            |state, value| {
+                if state.net.is_some() {
+                    return Err("the server decides the tick rate".to_string());
+                }
+                not_recording(state)?;
+                let tick_rate: f32 = value.parse().map_err(|_| "not a number")?;
+                if !tick_rate.is_finite() || tick_rate <= 0.0 {
+                    return Err("has to be a number above 0".to_string());
+                }
+                state.sim.set_tick_rate(tick_rate);
+                Ok(())
+            }
+        )
+        .variable(
+            "debug",
+            "chunk outlines instead of the ground",
+            |state| state.debug.to_string(),
+            |state, value| {
+                state.debug = value.parse().map_err(|_| "not true or false")?;
+                Ok(())
+            }
+        )
+        .variable(
+            "overlay",
+            "the debug overlay in the corner",
+            |state| state.overlay.to_string(),
+            |state, value| {
+                state.overlay = value.parse().map_err(|_| "not true or false")?;
+                Ok(())
+            }
+        )
+}
+
+fn editor(state: &Game) -> Editor {
+    Editor { path: state.world_path.clone(), painting: false }
+}
+
+//movement keys fly the camera, the left button builds walls and the right one takes them away
+fn update_editor(app: &mut App, state: &mut Game, editor: &mut Editor) -> Transition<Screen> {
+    let keyboard = &app.keyboard;
+    if state.bindings.was_pressed(keyboard, Action::Pause) || state.bindings.was_pressed(keyboard, Action::OpenEditor) {
+        return Transition::Pop;
+    }
+    if state.bindings.was_pressed(keyboard, Action::ToggleConsole) {
+        return Transition::Push(Screen::Console);
+    }
+    if state.bindings.was_pressed(keyboard, Action::Save) {
+        match state.sim.terrain.save(&editor.path) {
+            Ok(()) => println!("wrote world {}", editor.path),
+            Err(e) => eprintln!("could not write world {}: {}", editor.path, e),
+        }
+    }
+    view_keys(app, state);
+    state.mouse_pos = app.mouse.position().into();
+    //a screen width every two seconds at any zoom
+    let speed = (2.0_f32).powi(state.render_size_pow as i32) / 2.0;
+    let movement = Player::get_player_input(app, &state.bindings);
+    if let Some(camera) = &mut state.camera {
+        *camera += movement * speed * app.timer.delta_f32();
+    }
+    let wall = match (app.mouse.left_is_down(), app.mouse.right_is_down()) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => {
+            editor.painting = true;
+            None
+        }
+    };
+    if let Some(wall) = wall.filter(|_| editor.painting && !state.ui.wants_mouse(state.mouse_pos)) {
+        let pos = state.mouse_world_pos;
+        if pos.x >= 0.0 && pos.y >= 0.0 {
+            let tile = Chunk::pos_to_tile(pos);
+            state.stale_chunks.extend(state.sim.terrain.set_wall(tile, wall));
+        }
+    }
+    Transition::Stay
+}
 
-    if app.keyboard.was_pressed(KeyCode::F11) {
+//zoom, fullscreen and the debug view, for playing and the editor
+fn view_keys(app: &mut App, state: &mut Game) {
+    let bindings = &state.bindings;
+    if bindings.was_pressed(&app.keyboard, Action::ToggleFullscreen) {
         let full = !app.window().is_fullscreen();
         app.window().set_fullscreen(full);
     }
-    if app.keyboard.was_pressed(KeyCode::O) && state.render_size_pow >= 7 {
+    let render = &state.config.render;
+    if bindings.was_pressed(&app.keyboard, Action::ZoomOut) && state.render_size_pow > render.min_zoom {
         state.render_size_pow -= 1;
     }
-    if app.keyboard.was_pressed(KeyCode::P) && state.render_size_pow <= 14 {
+    if bindings.was_pressed(&app.keyboard, Action::ZoomIn) && state.render_size_pow < render.max_zoom {
         state.render_size_pow += 1;
     }
-    state.debug = app.keyboard.is_down(KeyCode::L);
+    if bindings.was_pressed(&app.keyboard, Action::ToggleDebug) {
+        state.debug = !state.debug;
+    }
+    if bindings.was_pressed(&app.keyboard, Action::ToggleOverlay) {
+        state.overlay = !state.overlay;
+    }
+    if bindings.was_pressed(&app.keyboard, Action::Screenshot) {
+        state.screenshot = true;
+    }
+}
 
-    state.mouse_pos = app.mouse.position().into();
+//keyboard, mouse and gamepad together
+fn read_input(app: &mut App, state: &mut Game) -> Input {
+    let mouse_pos: Vec2 = app.mouse.position().into();
+    if mouse_pos != state.mouse_pos {
+        state.gamepad_aim = None;
+    }
+    state.mouse_pos = mouse_pos;
 
-    if state.player.update(app) {
-        state.chunks.iter_mut().for_each(|c| {
-            let (x, y) = Chunk::pos_to_coords(state.player.pos().vec());
-            let (cx, cy) = c.coords();
-            let lod = (
-                i32::max(((x as i32) - (cx as i32)).abs() - 1, ((y as i32) - (cy as i32)).abs()) - 1
-            ).clamp(0, 7);
-            c.set_lod(lod as u8);
-        });
+    #[cfg(feature = "gamepad")]
+    let pad = state.gamepads.as_mut().and_then(|gamepads| gamepads.poll());
+    #[cfg(not(feature = "gamepad"))]
+    let pad: Option<GamepadState> = None;
+    let mut movement = Player::get_player_input(app, &state.bindings);
+    if let Some(pad) = pad {
+        if movement.length() == 0.0 {
+            movement = state.bindings.gamepad.movement(&pad);
+        }
+        if let Some(dir) = state.bindings.gamepad.aim(&pad) {
+            state.gamepad_aim = Some(dir);
+        }
+    }
+    let aim = match state.gamepad_aim {
+        Some(dir) => state.sim.player_pos() + dir * 100.0,
+        None => state.mouse_world_pos,
+    };
+    Input {
+        movement,
+        aim: Some(aim),
+        //clicks on the debug panel are not for the player
+        move_to: (app.mouse.left_was_pressed() && !state.ui.wants_mouse(mouse_pos)).then_some(state.mouse_world_pos),
     }
-    state.fps = format!("{:.0} fps \nupdate time: {:.2}ms", app.timer.fps(), app.date_now() - time);
 }
 
-fn angle_between_points(point1: &Vec2, point2: &Vec2) -> f32 {
-    let delta_x = point2.x - point1.x;
-    let delta_y = point2.y - point1.y;
+//steps the simulation and everything that goes with it
+fn update_game(app: &mut App, state: &mut Game, input: &Input) {
+    let time = app.date_now();
 
-    delta_y.atan2(delta_x) * 57.2957795
+    let player = state.sim.player();
+    state.sim.apply_input(input);
+    for _ in 0..state.sim.advance(app.timer.delta_f32()) {
+        let replayed = state.replay.as_ref().and_then(|replay| replay.ticks.get(state.replay_tick));
+        if let Some(replayed) = replayed {
+            replayed.apply(&mut state.sim);
+            state.replay_tick += 1;
+        }
+        if let Some((replay, _)) = &mut state.recording {
+            replay.record(&state.sim);
+        }
+        state.sim.tick();
+        if let Some(net) = &mut state.net {
+            if let Err(e) = net.tick(&state.sim.world, player) {
+                eprintln!("network error: {}", e);
+            }
+        }
+    }
+    if let Some(net) = &mut state.net {
+        if let Err(e) = net.poll(&mut state.sim.world, &state.sim.terrain, player) {
+            eprintln!("network error: {}", e);
+        }
+        //the server's ground replaces ours once it welcomed us
+        if let (Some(seed), Some(area_size)) = (net.seed(), net.area_size()) {
+            if seed != state.sim.seed() || area_size != state.sim.terrain.area_size() {
+                state.sim.regenerate(seed, area_size);
+                state.terrain_changed = true;
+            }
+        }
+        net.interpolate(&mut state.sim.world);
+    }
+
+    animation_system(&mut state.sim.world, app.timer.delta_f32());
+
+    state.since_watch += app.timer.delta_f32();
+    if state.since_watch >= WATCH_INTERVAL {
+        state.since_watch = 0.0;
+        state.stale_assets.extend(state.assets.changed());
+        if state.config_file.changed() {
+            reload_config(app, state);
+        }
+    }
+
+    let delta = state.sim.delta();
+    let chunk_changed = match (
+        state.sim.world.players.get_mut(player),
+        state.sim.world.transforms.get(player),
+    ) {
+        (Some(player), Some(transform)) => player.check_chunk_change(transform, delta),
+        _ => false,
+    };
+    if chunk_changed {
+        update_lods(state);
+    }
+
+    state.update_ms = (app.date_now() - time) as f32;
+}
+
+//chunks further from the player get less detail, unless the console fixed the level
+fn update_lods(state: &mut Game) {
+    let (x, y) = Chunk::pos_to_coords(state.sim.player_pos());
+    let forced = state.forced_lod;
+    state.chunks.iter_mut().for_each(|c| {
+        let (cx, cy) = c.coords();
+        let lod = (
+            i32::max(((x as i32) - (cx as i32)).abs() - 1, ((y as i32) - (cy as i32)).abs()) - 1
+        ).clamp(0, 7);
+        c.set_lod(forced.unwrap_or(lod as u8));
+    });
+}
+
+//applies a changed settings file, world settings only take effect on the next start
+fn reload_config(app: &mut App, state: &mut Game) {
+    let path = state.config_file.path().display().to_string();
+    let config = match load_config(&path, &state.overrides) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("kept the old settings, {}", e);
+            return;
+        }
+    };
+    apply_window(app, &state.config.window, &config.window);
+    if state.config.render != config.render {
+        //chunks are made again with the new texture sizes on the next draw
+        state.chunks.clear();
+        state.terrain_changed = true;
+    }
+    state.render_size_pow = config.clamp_zoom(state.render_size_pow);
+    state.config = config;
+    println!("reloaded {}", path);
+}
+
+//resizes the window and goes in or out of fullscreen, vsync and high dpi wait for the next start
+fn apply_window(app: &mut App, old: &WindowSettings, new: &WindowSettings) {
+    if (old.width, old.height) != (new.width, new.height) {
+        app.window().set_size(new.width, new.height);
+    }
+    if old.fullscreen != new.fullscreen {
+        app.window().set_fullscreen(new.fullscreen);
+    }
+}
+
+//swaps in the assets that changed on disk, a file that can not be loaded keeps the old one
+fn reload_assets(gfx: &mut Graphics, state: &mut Game) {
+    for asset in std::mem::take(&mut state.stale_assets) {
+        let bytes = match state.assets.read(asset) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("could not reload {}: {}", asset.file_name(), e);
+                continue;
+            }
+        };
+        let loaded = match asset {
+            AssetFile::Font => gfx.create_font(&bytes).map(|font| state.font = font),
+            AssetFile::Atlas =>
+                gfx
+                    .create_texture()
+                    .from_image(&bytes)
+                    .build()
+                    .map(|atlas| {
+                        state.textures[0] = atlas;
+                        //every chunk and its low-res copy is baked from the atlas
+                        state.terrain_changed = true;
+                    }),
+            AssetFile::PlayerSheet =>
+                gfx
+                    .create_texture()
+                    .from_image(&bytes)
+                    .build()
+                    .map(|sheet| {
+                        for (_, renderable) in state.sim.world.renderables.iter_mut() {
+                            if let Renderable::Animated { texture, .. } = renderable {
+                                *texture = sheet.clone();
+                            }
+                        }
+                        if let Some(net) = &mut state.net {
+                            net.set_remote_sheet(sheet);
+                        }
+                    }),
+        };
+        match loaded {
+            Ok(()) => println!("reloaded {}", asset.file_name()),
+            Err(e) => eprintln!("could not reload {}: {}", asset.file_name(), e),
+        }
+    }
+}
+
+fn create_chunks(
+    gfx: &mut Graphics,
+    textures: &[Texture],
+    terrain: &Terrain,
+    settings: &RenderSettings
+) -> Vec<Chunk> {
+    (0..terrain.area_size() * terrain.area_size())
+        .map(|index| create_chunk(gfx, textures, terrain, settings, index))
+        .collect()
+}
+
+//the chunk at `index` in row order with its low-res copy baked
+fn create_chunk(
+    gfx: &mut Graphics,
+    textures: &[Texture],
+    terrain: &Terrain,
+    settings: &RenderSettings,
+    index: usize
+) -> Chunk {
+    let (x, y) = (index % terrain.area_size(), index / terrain.area_size());
+    let mut chunk = Chunk::new(gfx, x, y, settings);
+    chunk.render_low_res(gfx, textures, terrain.chunk((x, y)).unwrap());
+    chunk
 }
 
 fn render_chunks(
     gfx: &mut Graphics,
-    state: &mut State,
-    draw: &mut Draw,
+    state: &mut Game,
     (x1, y1): (usize, usize),
     (x2, y2): (usize, usize)
 ) {
-    'main: for y in y1..usize::min(y2, AREA_SIZE - 1) + 1 {
-        for x in x1..usize::min(x2, AREA_SIZE - 1) + 1 {
-            let index = x + y * AREA_SIZE;
-            if index < 0 || (index as usize) >= state.chunks.len() {
+    let area_size = state.sim.terrain.area_size();
+    'main: for y in y1..usize::min(y2, area_size - 1) + 1 {
+        for x in x1..usize::min(x2, area_size - 1) + 1 {
+            let index = x + y * area_size;
+            if index >= state.chunks.len() {
                 continue;
             }
-            if state.chunks[index as usize].needs_redraw() {
-                state.chunks[index as usize].redraw(gfx, &state.textures);
+            if state.chunks[index].needs_redraw() {
+                let tiles = state.sim.terrain.chunk((x, y)).unwrap();
+                state.chunks[index].redraw(gfx, &state.textures, tiles);
                 break 'main;
             }
         }
@@ -139,61 +1268,456 @@ fn render_chunks(
 }
 
 use notan::math::*;
-fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
+//every screen that shows, the ones below an overlay first, only the top one gets the input
+fn draw(app: &mut App, assets: &mut Assets, gfx: &mut Graphics, state: &mut State) {
+    let State { screens, session } = state;
+    if let Some(Screen::Loading(loading)) = screens.top_mut() {
+        if let Some(game) = advance_loading(app, gfx, loading) {
+            session.game = Some(Box::new(game));
+            screens.apply(Transition::Replace(Screen::Playing), session);
+        }
+    }
+    let input = UiInput::from_app(app, &std::mem::take(&mut session.typed));
+    let covered = UiInput::none();
+    let visible = screens.visible_mut();
+    let top = visible.len().saturating_sub(1);
+    let mut transition = Transition::Stay;
+    for (i, screen) in visible.iter_mut().enumerate() {
+        let input = if i == top { &input } else { &covered };
+        let next = match screen {
+            Screen::MainMenu(ui) => {
+                render_bg(gfx, &session.background);
+                draw_main_menu(app, assets, gfx, ui, input, session)
+            }
+            Screen::Loading(loading) => {
+                draw_loading(gfx, loading, session);
+                Transition::Stay
+            }
+            Screen::Playing => {
+                if let Some(game) = &mut session.game {
+                    draw_game(app, gfx, game, input);
+                }
+                Transition::Stay
+            }
+            Screen::Paused(ui) => draw_pause_menu(app, gfx, ui, input, session),
+            Screen::Settings(settings) => draw_settings(app, gfx, settings, input, session),
+            Screen::Bindings(rebind) => {
+                if let Some(game) = &session.game {
+                    let mut draw = gfx.create_draw();
+                    rebind.render(&mut draw, &session.font, &game.bindings);
+                    gfx.render(&draw);
+                }
+                Transition::Stay
+            }
+            Screen::Editor(editor) => {
+                if let Some(game) = &mut session.game {
+                    draw_game(app, gfx, game, input);
+                }
+                let save = session.game
+                    .as_ref()
+                    .and_then(|game| game.bindings.get(Action::Save).first())
+                    .map_or("nothing".to_string(), |binding| binding.to_string());
+                let mut draw = gfx.create_draw();
+                let (_, height) = draw.size();
+                draw.text(
+                    &session.font,
+                    &format!(
+                        "editor: movement keys fly, left button builds walls, right button removes them, {} saves to {}, escape goes back",
+                        save,
+                        editor.path
+                    )
+                )
+                    .position(10.0, height - 24.0)
+                    .size(14.0);
+                gfx.render(&draw);
+                Transition::Stay
+            }
+            Screen::Console => {
+                if let Some(game) = &mut session.game {
+                    session.console.update(input, &session.commands, game);
+                }
+                let mut draw = gfx.create_draw();
+                session.console.render(&mut draw, &session.font);
+                gfx.render(&draw);
+                Transition::Stay
+            }
+        };
+        if i == top {
+            transition = next;
+        }
+    }
+    screens.apply(transition, session);
+}
+
+//a column of buttons in the middle of the screen, the one clicked or entered this frame
+fn draw_menu(
+    gfx: &mut Graphics,
+    font: &Font,
+    state: &mut UiState,
+    input: &UiInput,
+    title: &str,
+    items: &[&'static str],
+    error: Option<&str>
+) -> Option<&'static str> {
+    let mut draw = gfx.create_draw();
+    let (width, _) = draw.size();
+    let mut chosen = None;
+    let mut ui = Ui::new(&mut draw, font, input, state);
+    ui.panel(title, ((width - MENU_WIDTH) / 2.0, 100.0), MENU_WIDTH, |ui| {
+        for item in items {
+            if ui.button(item) {
+                chosen = Some(*item);
+            }
+        }
+        for line in error.iter().flat_map(|error| error.lines()) {
+            ui.colored_label(line, Color::RED);
+        }
+    });
+    drop(ui);
+    gfx.render(&draw);
+    chosen
+}
+
+fn draw_main_menu(
+    app: &mut App,
+    assets: &mut Assets,
+    gfx: &mut Graphics,
+    ui: &mut UiState,
+    input: &UiInput,
+    session: &mut Session
+) -> Transition<Screen> {
+    let error = session.menu_error.as_deref();
+    match draw_menu(gfx, &session.font, ui, input, "Indifi", &["play", "settings", "quit"], error) {
+        Some("play") => {
+            match start_loading(assets, session) {
+                Ok(loading) => {
+                    session.menu_error = None;
+                    Transition::Replace(Screen::Loading(Box::new(loading)))
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    session.menu_error = Some(e);
+                    Transition::Stay
+                }
+            }
+        }
+        Some("settings") => Transition::Push(Screen::Settings(Box::new(Settings::new(session)))),
+        Some("quit") => {
+            app.exit();
+            Transition::Stay
+        }
+        _ => Transition::Stay,
+    }
+}
+
+fn dim_screen(gfx: &mut Graphics) {
+    let mut draw = gfx.create_draw();
+    let (width, height) = draw.size();
+    draw.rect((0.0, 0.0), (width, height)).color(Color::new(0.0, 0.0, 0.0, 0.6));
+    gfx.render(&draw);
+}
+
+//the editor only shows up offline
+fn draw_pause_menu(
+    app: &mut App,
+    gfx: &mut Graphics,
+    ui: &mut UiState,
+    input: &UiInput,
+    session: &mut Session
+) -> Transition<Screen> {
+    let Some(game) = &session.game else {
+        return Transition::Stay;
+    };
+    dim_screen(gfx);
+    let items: &[&'static str] = if game.net.is_some() || game.recording.is_some() {
+        &["resume", "settings", "key bindings", "main menu", "quit"]
+    } else {
+        &["resume", "settings", "key bindings", "editor", "main menu", "quit"]
+    };
+    match draw_menu(gfx, &session.font, ui, input, "Paused", items, None) {
+        Some("resume") => Transition::Pop,
+        Some("settings") => Transition::Push(Screen::Settings(Box::new(Settings::new(session)))),
+        Some("key bindings") => Transition::Push(Screen::Bindings(RebindScreen::default())),
+        Some("editor") => Transition::Replace(Screen::Editor(editor(game))),
+        Some("main menu") => Transition::Reset(Screen::MainMenu(UiState::default().with_keyboard())),
+        Some("quit") => {
+            app.exit();
+            Transition::Stay
+        }
+        _ => Transition::Stay,
+    }
+}
+
+//escape goes back without saving unless a text field has it
+fn draw_settings(
+    app: &mut App,
+    gfx: &mut Graphics,
+    settings: &mut Settings,
+    input: &UiInput,
+    session: &mut Session
+) -> Transition<Screen> {
+    let editing = settings.ui.is_editing();
+    dim_screen(gfx);
+    let mut draw = gfx.create_draw();
+    let (width, _) = draw.size();
+    let (mut save, mut back) = (false, false);
+    let render = &settings.config.render;
+    let zoom_range = (render.min_zoom as f32, render.max_zoom as f32);
+    let mut ui = Ui::new(&mut draw, &session.font, input, &mut settings.ui);
+    ui.panel("Settings", ((width - SETTINGS_WIDTH) / 2.0, 60.0), SETTINGS_WIDTH, |ui| {
+        ui.text_field("name", &mut settings.name);
+        ui.columns(2, |ui| {
+            ui.text_field("width", &mut settings.width);
+            ui.text_field("height", &mut settings.height);
+        });
+        let window = &mut settings.config.window;
+        ui.checkbox("fullscreen", &mut window.fullscreen);
+        ui.checkbox("vsync, on the next start", &mut window.vsync);
+        ui.checkbox("high dpi, on the next start", &mut window.high_dpi);
+        ui.slider("zoom of new profiles", &mut settings.zoom, zoom_range, 1.0);
+        ui.slider("world size, in new worlds", &mut settings.area_size, (1.0, MAX_AREA_SIZE as f32), 1.0);
+        if let Some(error) = &settings.error {
+            for line in error.lines() {
+                ui.colored_label(line, Color::RED);
+            }
+        }
+        ui.columns(2, |ui| {
+            save = ui.button("save");
+            back = ui.button("back");
+        });
+    });
+    drop(ui);
+    gfx.render(&draw);
+
+    if save {
+        match save_settings(app, settings, session) {
+            Ok(()) => return Transition::Pop,
+            Err(e) => settings.error = Some(e),
+        }
+    }
+    if back || (!editing && input.keys.contains(&KeyCode::Escape)) { Transition::Pop } else { Transition::Stay }
+}
+
+//checks the settings and writes only what was changed here into the file, so its comments stay
+//and options from the command line are not saved with it, a running game picks the file up like
+//any other change
+fn save_settings(app: &mut App, settings: &Settings, session: &mut Session) -> Result<(), String> {
+    let (old, edited) = (&session.config, &settings.config);
+    let changed = [
+        ("window.width", old.window.width.to_string(), settings.width.trim().to_string()),
+        ("window.height", old.window.height.to_string(), settings.height.trim().to_string()),
+        ("window.fullscreen", old.window.fullscreen.to_string(), edited.window.fullscreen.to_string()),
+        ("window.vsync", old.window.vsync.to_string(), edited.window.vsync.to_string()),
+        ("window.high_dpi", old.window.high_dpi.to_string(), edited.window.high_dpi.to_string()),
+        ("render.zoom", old.render.zoom.to_string(), (settings.zoom as u8).to_string()),
+        ("world.area_size", old.world.area_size.to_string(), (settings.area_size as usize).to_string()),
+    ];
+    let changes: Vec<(String, String)> = changed
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(key, _, new)| (key.to_string(), new))
+        .collect();
+
+    //this run keeps its options with the changes on top
+    let mut config = session.config.clone();
+    for (key, value) in &changes {
+        let name = key.split('.').nth(1).unwrap_or(key);
+        config.set(key, value).map_err(|e| format!("{}: {}", name, e))?;
+    }
+    config.validate()?;
+
+    let path = &session.args.config;
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(format!("could not read {}: {}", path, e));
+        }
+    };
+    let text = Config::edit_text(&text, &changes).map_err(|e| format!("could not change {}: {}", path, e))?;
+    Config::parse(&text)
+        .and_then(|file| file.validate())
+        .map_err(|e| format!("{} would not be valid:\n{}", path, e))?;
+    std::fs::write(path, text).map_err(|e| format!("could not write {}: {}", path, e))?;
+
+    apply_window(app, &session.config.window, &config.window);
+    session.config = config;
+    let name = settings.name.trim();
+    session.args.name = (!name.is_empty()).then(|| name.to_string());
+    Ok(())
+}
+
+fn draw_game(app: &mut App, gfx: &mut Graphics, state: &mut Game, input: &UiInput) {
     let time = app.date_now();
+    state.frame_times.push(app.timer.delta_f32() * 1000.0);
+    continue_export(gfx, state);
+
+    reload_assets(gfx, state);
+    for coords in std::mem::take(&mut state.stale_chunks) {
+        let index = coords.0 + coords.1 * state.sim.terrain.area_size();
+        if let (Some(chunk), Some(tiles)) = (state.chunks.get_mut(index), state.sim.terrain.chunk(coords)) {
+            chunk.render_low_res(gfx, &state.textures, tiles);
+            chunk.invalidate();
+        }
+    }
+    if state.terrain_changed {
+        state.terrain_changed = false;
+        let area_size = state.sim.terrain.area_size();
+        if state.chunks.len() != area_size * area_size {
+            state.chunks = create_chunks(gfx, &state.textures, &state.sim.terrain, &state.config.render);
+        }
+        for chunk in state.chunks.iter_mut() {
+            let tiles = state.sim.terrain.chunk(chunk.coords()).unwrap();
+            chunk.render_low_res(gfx, &state.textures, tiles);
+            chunk.invalidate();
+        }
+    }
 
     let mut draw = gfx.create_draw();
     draw.clear(Color::BLACK);
     draw.set_projection(Some(world_projection(gfx.size(), state.render_size_pow).0));
-    draw.transform().set(
-        Mat3::from_translation(
-            Vec2::new(-state.player.pos().x(), -state.player.pos().y())
-        )
-    );
+    let player_pos = state.camera.unwrap_or_else(|| state.sim.player_render_pos());
+    draw.transform().set(Mat3::from_translation(-player_pos));
 
     let (x1, y1) = Chunk::pos_to_coords(draw.screen_to_world_position(0.0, 0.0));
     let (x2, y2) = Chunk::pos_to_coords(
         draw.screen_to_world_position(gfx.size().0 as f32, gfx.size().1 as f32)
     );
 
-    render_chunks(gfx, state, &mut draw, (x1, y1), (x2, y2));
+    render_chunks(gfx, state, (x1, y1), (x2, y2));
 
-    state.player.set_desired_rotation(
-        angle_between_points(
-            &state.player.pos().vec2(),
-            &draw.screen_to_world_position(state.mouse_pos.x, state.mouse_pos.y)
-        )
-    );
+    state.mouse_world_pos = draw.screen_to_world_position(state.mouse_pos.x, state.mouse_pos.y);
 
-    'main: for y in y1..usize::min(y2, AREA_SIZE - 1) + 1 {
-        for x in x1..usize::min(x2, AREA_SIZE - 1) + 1 {
-            let index = x + y * AREA_SIZE;
-            state.chunks[index as usize].render(&mut draw, state.debug);
+    let area_size = state.sim.terrain.area_size();
+    for y in y1..usize::min(y2, area_size - 1) + 1 {
+        for x in x1..usize::min(x2, area_size - 1) + 1 {
+            let index = x + y * area_size;
+            state.chunks[index].render(&mut draw, state.debug);
         }
     }
 
-    state.player.render(&mut draw);
+    //clamped to the world like the chunks, zoomed far out the range would be mostly empty cells
+    let last = area_size - 1;
+    let visible = ((x1.min(last), y1.min(last)), (x2.min(last), y2.min(last)));
+    render_system(&state.sim.world, &mut draw, visible.0, visible.1, state.sim.alpha());
+    name_system(&state.sim.world, &mut draw, &state.font, visible.0, visible.1, state.sim.alpha());
 
     gfx.render(&draw);
 
-    let mut draw_ui = gfx.create_draw();
-
-    let draw_fps = format!("draw time: {:.2}ms", app.date_now() - time);
-    draw_ui
-        .text(
-            &state.font,
-            &format!(
-                "x: {:.2}\ny: {:.2}\n{}\n{}\nResolution: {:?}\nScale: {}",
-                state.player.pos().x(),
-                state.player.pos().y(),
-                &state.fps,
-                &draw_fps,
-                gfx.size(),
-                state.render_size_pow
-            )
-        )
-        .position(10.0, 10.0)
-        .size(14.0);
+    let draw_ms = (app.date_now() - time) as f32;
+    let overlay = draw_overlay(app, gfx, state, input, draw_ms, player_pos, visible);
+    gfx.render(&overlay);
+
+    if std::mem::take(&mut state.screenshot) {
+        match save_screenshot(gfx, &[&draw, &overlay]) {
+            Ok(path) => println!("wrote screenshot {}", path.display()),
+            Err(e) => eprintln!("could not take a screenshot: {}", e),
+        }
+    }
+}
+
+//the pixels of a texture, rows from the top
+fn read_texture(gfx: &mut Graphics, texture: &RenderTexture) -> Result<PixelImage, String> {
+    let (width, height) = (texture.width() as u32, texture.height() as u32);
+    let mut pixels = vec![0; (width as usize) * (height as usize) * 4];
+    gfx.read_pixels(texture).read_to(&mut pixels)?;
+    PixelImage::from_pixels(width, height, pixels, true)
+}
+
+//the screen can not be read back, so the frame is drawn again into a texture of its size
+fn save_screenshot(gfx: &mut Graphics, draws: &[&Draw]) -> Result<PathBuf, String> {
+    let (width, height) = gfx.size();
+    let texture = gfx.create_render_texture(width, height).build()?;
+    for draw in draws {
+        gfx.render_to(&texture, *draw);
+    }
+    let image = read_texture(gfx, &texture)?;
+    let path = next_free_path(SCREENSHOT_DIR, "screenshot");
+    image.save_png(&path).map_err(|e| format!("could not write {}: {}", path.display(), e))?;
+    Ok(path)
+}
 
-    gfx.render(&draw_ui);
+//renders the next chunk of a running export, one a frame so a big one does not stall the game
+fn continue_export(gfx: &mut Graphics, state: &mut Game) {
+    let Some(mut writer) = state.export.take() else {
+        return;
+    };
+    match export_chunk(gfx, state, &mut writer) {
+        Ok(()) if writer.next_chunk().is_some() => {
+            state.export = Some(writer);
+        }
+        Ok(()) =>
+            match writer.finish() {
+                Ok(path) => println!("exported the world to {}", path.display()),
+                Err(e) => eprintln!("could not finish the world export: {}", e),
+            }
+        Err(e) => eprintln!("could not export the world: {}", e),
+    }
+}
+
+fn export_chunk(gfx: &mut Graphics, state: &Game, writer: &mut ExportWriter) -> Result<(), String> {
+    let Some(coords) = writer.next_chunk() else {
+        return Ok(());
+    };
+    let chunk = state.chunks.get(coords.0 + coords.1 * state.sim.terrain.area_size());
+    let image = match (chunk, state.sim.terrain.chunk(coords)) {
+        (Some(chunk), Some(tiles)) => {
+            let texture = chunk.render_at(gfx, &state.textures, tiles, writer.export().lod);
+            Some(read_texture(gfx, &texture)?)
+        }
+        _ => None,
+    };
+    writer.add_chunk(image.as_ref()).map_err(|e| format!("could not write {}: {}", writer.path().display(), e))
+}
+
+//timings, chunk stats and what is under the mouse, the ui frame is made even when the overlay is
+//hidden so it lets go of the mouse
+fn draw_overlay(
+    app: &App,
+    gfx: &mut Graphics,
+    state: &mut Game,
+    input: &UiInput,
+    draw_ms: f32,
+    camera: Vec2,
+    ((x1, y1), (x2, y2)): ((usize, usize), (usize, usize))
+) -> Draw {
+    let frames = &state.frame_times;
+    //a 60 fps frame is always on the graph
+    let target_ms = 1000.0 / 60.0;
+    let graph_max = frames.max().max(target_ms * 2.0);
+    let chunks = ChunkStats::of(&state.chunks);
+    let lods: Vec<String> = chunks.per_lod.iter().map(|count| count.to_string()).collect();
+    let pos = state.mouse_world_pos;
+    let tile = (pos.x >= 0.0 && pos.y >= 0.0).then(|| Chunk::pos_to_tile(pos));
+    let tile_text = match tile.and_then(|tile| state.sim.terrain.visible_tile(tile).map(|id| (tile, id))) {
+        Some(((x, y), id)) => format!("tile ({}, {}): {}", x, y, id),
+        None => "tile: outside the world".to_string(),
+    };
+    let render = &state.config.render;
+    let zoom_range = (render.min_zoom as f32, render.max_zoom as f32);
+    let mut zoom = state.render_size_pow as f32;
+
+    let mut draw = gfx.create_draw();
+    let mut ui = Ui::new(&mut draw, &state.font, input, &mut state.ui);
+    if state.overlay {
+        ui.panel("", (10.0, 10.0), 280.0, |ui| {
+            ui.label(
+                &format!("{:.0} fps, frame {:.1}ms, worst {:.1}ms", app.timer.fps(), frames.average(), frames.max())
+            );
+            ui.graph(frames.iter(), graph_max, Some(target_ms));
+            ui.label(&format!("update {:.0}ms, draw {:.0}ms", state.update_ms, draw_ms));
+            ui.label(&format!("x: {:.2} y: {:.2}", camera.x, camera.y));
+            ui.label(&format!("resolution: {:?}", gfx.size()));
+            ui.label(&format!("chunks x {}..{} y {}..{}", x1, x2, y1, y2));
+            ui.label(&format!("chunks per lod: {}", lods.join(" / ")));
+            ui.label(&format!("redraws pending: {}", chunks.pending));
+            ui.label(&format!("chunk textures: {}", format_bytes(chunks.texture_bytes)));
+            ui.label(&tile_text);
+            ui.checkbox("chunk outlines", &mut state.debug);
+            ui.slider("zoom", &mut zoom, zoom_range, 1.0);
+        });
+    }
+    drop(ui);
+    state.render_size_pow = zoom as u8;
+    draw
 }
@@ -0,0 +1,649 @@
+use crate::*;
+use std::collections::{ HashMap, VecDeque };
+use std::io;
+use std::net::{ SocketAddr, ToSocketAddrs, UdpSocket };
+use std::time::{ Duration, Instant };
+use notan::random::rand::random;
+
+pub const DEFAULT_PORT: u16 = 7777;
+pub const TICK_RATE: f32 = 30.0;
+pub const TICK_DELTA: f32 = 1.0 / TICK_RATE;
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+//remote players are shown this far in the past so there are two snapshots to blend between
+pub const INTERPOLATION_DELAY: Duration = Duration::from_millis(100);
+const HELLO_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_PACKET_SIZE: usize = 65507;
+//tag, tick, part, parts and player count of a snapshot
+const SNAPSHOT_HEADER_SIZE: usize = 1 + 4 + 2 + 2 + 2;
+//one player in a snapshot without the bytes of its name
+const PLAYER_SNAPSHOT_SIZE: usize = 2 + 1 + 5 * 4 + 3 * 4 + 4;
+//unacknowledged inputs are resent with every new one, so a lost packet costs nothing
+const MAX_RESENT_INPUTS: usize = 16;
+const MAX_PENDING_INPUTS: usize = 256;
+
+pub struct PlayerSnapshot {
+    pub id: u16,
+    pub display_name: String,
+    pub x: f32,
+    pub y: f32,
+    pub degrees: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub color: [f32; 3],
+    //sequence number of the last input of this player the server has applied
+    pub last_input: u32,
+}
+
+#[derive(Copy, Clone)]
+pub struct InputCommand {
+    pub seq: u32,
+    pub movement: Vec2,
+    pub desired_rotation: Angle,
+}
+
+impl InputCommand {
+    //one fixed tick of movement, the same step movement_system takes at TICK_RATE
+    pub fn apply(&self, transform: &mut TransformPR, terrain: &Terrain) {
+        let surface = terrain.surface_at(transform.position().vec());
+        transform.update(TICK_DELTA, self.movement, self.desired_rotation, surface);
+    }
+}
+
+pub enum ClientMessage {
+    Hello {
+        display_name: String,
+        color: [f32; 3],
+    },
+    Input {
+        commands: Vec<InputCommand>,
+    },
+    Bye,
+}
+
+pub enum ServerMessage {
+    //`seed` and `area_size` are the server's world, the client has to simulate the same ground
+    Welcome {
+        id: u16,
+        seed: u64,
+        area_size: u16,
+    },
+    //a snapshot too big for one packet is split into `parts`, each with some of the players
+    Snapshot {
+        tick: u32,
+        part: u16,
+        parts: u16,
+        players: Vec<PlayerSnapshot>,
+    },
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn new(tag: u8) -> Self {
+        Writer(vec![tag])
+    }
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+    fn f32(&mut self, value: f32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+    fn color(&mut self, color: [f32; 3]) {
+        color.iter().for_each(|c| self.f32(*c));
+    }
+    //names are cut to 255 bytes, on a char boundary
+    fn str(&mut self, value: &str) {
+        let mut len = value.len().min(255);
+        while !value.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.0.push(len as u8);
+        self.0.extend_from_slice(&value.as_bytes()[..len]);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8).map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+    }
+    fn f32(&mut self) -> Option<f32> {
+        self.take(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    fn color(&mut self) -> Option<[f32; 3]> {
+        Some([self.f32()?, self.f32()?, self.f32()?])
+    }
+    fn str(&mut self) -> Option<String> {
+        let len = self.u8()? as usize;
+        self.take(len).map(|b| String::from_utf8_lossy(b).into_owned())
+    }
+}
+
+impl ClientMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            ClientMessage::Hello { display_name, color } => {
+                let mut w = Writer::new(0);
+                w.str(display_name);
+                w.color(*color);
+                w.0
+            }
+            ClientMessage::Input { commands } => {
+                let mut w = Writer::new(1);
+                w.0.push(commands.len().min(255) as u8);
+                for command in commands.iter().take(255) {
+                    w.u32(command.seq);
+                    w.f32(command.movement.x);
+                    w.f32(command.movement.y);
+                    w.f32(command.desired_rotation.degrees());
+                }
+                w.0
+            }
+            ClientMessage::Bye => Writer::new(2).0,
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut r = Reader { bytes };
+        match r.u8()? {
+            0 => Some(ClientMessage::Hello { display_name: r.str()?, color: r.color()? }),
+            1 => {
+                let count = r.u8()?;
+                let mut commands = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    commands.push(InputCommand {
+                        seq: r.u32()?,
+                        movement: Vec2::new(r.f32()?, r.f32()?),
+                        desired_rotation: Angle::from_degrees(r.f32()?),
+                    });
+                }
+                Some(ClientMessage::Input { commands })
+            }
+            2 => Some(ClientMessage::Bye),
+            _ => None,
+        }
+    }
+}
+
+impl ServerMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            ServerMessage::Welcome { id, seed, area_size } => {
+                let mut w = Writer::new(10);
+                w.u16(*id);
+                w.u64(*seed);
+                w.u16(*area_size);
+                w.0
+            }
+            ServerMessage::Snapshot { tick, part, parts, players } => {
+                let mut w = Writer::new(11);
+                w.u32(*tick);
+                w.u16(*part);
+                w.u16(*parts);
+                w.u16(players.len() as u16);
+                for player in players {
+                    w.u16(player.id);
+                    w.str(&player.display_name);
+                    w.f32(player.x);
+                    w.f32(player.y);
+                    w.f32(player.degrees);
+                    w.f32(player.vx);
+                    w.f32(player.vy);
+                    w.color(player.color);
+                    w.u32(player.last_input);
+                }
+                w.0
+            }
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut r = Reader { bytes };
+        match r.u8()? {
+            10 => {
+                let (id, seed, area_size) = (r.u16()?, r.u64()?, r.u16()?);
+                //the client builds a world this big, a server can not make it build a huge one
+                if !(1..=MAX_AREA_SIZE).contains(&(area_size as usize)) {
+                    return None;
+                }
+                Some(ServerMessage::Welcome { id, seed, area_size })
+            }
+            11 => {
+                let (tick, part, parts) = (r.u32()?, r.u16()?, r.u16()?);
+                if part >= parts {
+                    return None;
+                }
+                let count = r.u16()?;
+                let mut players = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    players.push(PlayerSnapshot {
+                        id: r.u16()?,
+                        display_name: r.str()?,
+                        x: r.f32()?,
+                        y: r.f32()?,
+                        degrees: r.f32()?,
+                        vx: r.f32()?,
+                        vy: r.f32()?,
+                        color: r.color()?,
+                        last_input: r.u32()?,
+                    });
+                }
+                Some(ServerMessage::Snapshot { tick, part, parts, players })
+            }
+            _ => None,
+        }
+    }
+
+    //every player at `tick` in as many snapshot parts as it takes to keep each in one packet
+    pub fn snapshots(tick: u32, players: Vec<PlayerSnapshot>) -> Vec<ServerMessage> {
+        let mut groups = vec![Vec::new()];
+        let mut size = SNAPSHOT_HEADER_SIZE;
+        for player in players {
+            let player_size = PLAYER_SNAPSHOT_SIZE + player.display_name.len().min(255);
+            if size + player_size > MAX_PACKET_SIZE {
+                groups.push(Vec::new());
+                size = SNAPSHOT_HEADER_SIZE;
+            }
+            size += player_size;
+            groups.last_mut().unwrap().push(player);
+        }
+        let parts = groups.len() as u16;
+        groups
+            .into_iter()
+            .enumerate()
+            .map(|(part, players)| ServerMessage::Snapshot { tick, part: part as u16, parts, players })
+            .collect()
+    }
+}
+
+//reads every pending datagram from a non-blocking socket
+pub(crate) fn receive_all(
+    socket: &UdpSocket,
+    mut handle: impl FnMut(std::net::SocketAddr, &[u8])
+) -> io::Result<()> {
+    let mut buffer = vec![0; MAX_PACKET_SIZE];
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((len, addr)) => handle(addr, &buffer[..len]),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                return Ok(());
+            }
+            //windows reports icmp port unreachable from earlier sends this way
+            Err(e) if e.kind() == io::ErrorKind::ConnectionReset => {}
+            Err(e) => {
+                return Err(e);
+            }
+        }
+    }
+}
+
+struct Sample {
+    time: Instant,
+    pos: Vec2,
+    velocity: Vec2,
+    degrees: f32,
+}
+
+struct Remote {
+    entity: Entity,
+    samples: VecDeque<Sample>,
+}
+
+//the parts of a split snapshot that arrived so far
+struct PartialSnapshot {
+    tick: u32,
+    received: Vec<bool>,
+    players: Vec<PlayerSnapshot>,
+}
+
+pub struct NetClient {
+    socket: UdpSocket,
+    id: Option<u16>,
+    //seed and area size of the server's world
+    world: Option<(u64, usize)>,
+    hello: Vec<u8>,
+    last_hello: Instant,
+    last_tick: Option<u32>,
+    partial: Option<PartialSnapshot>,
+    remotes: HashMap<u16, Remote>,
+    seq: u32,
+    //inputs applied locally but not yet confirmed by the server, oldest first
+    pending: VecDeque<InputCommand>,
+    //how other players are drawn, circles if None
+    remote_sprite: Option<(Texture, SpriteSheet)>,
+}
+
+impl NetClient {
+    pub fn connect(addr: impl ToSocketAddrs, display_name: &str, color: Color) -> io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(addr)?;
+        socket.set_nonblocking(true)?;
+        let hello = (ClientMessage::Hello {
+            display_name: display_name.to_string(),
+            color: color.rgb(),
+        }).encode();
+        socket.send(&hello)?;
+        Ok(NetClient {
+            socket,
+            id: None,
+            world: None,
+            hello,
+            last_hello: Instant::now(),
+            last_tick: None,
+            partial: None,
+            remotes: HashMap::new(),
+            seq: 0,
+            pending: VecDeque::new(),
+            remote_sprite: None,
+        })
+    }
+
+    //players that join after this are drawn from the sprite sheet
+    pub fn set_remote_sprite(&mut self, texture: Texture, sheet: SpriteSheet) {
+        self.remote_sprite = Some((texture, sheet));
+    }
+
+    //the texture other players are drawn with from now on, the sheet stays the same
+    pub fn set_remote_sheet(&mut self, texture: Texture) {
+        if let Some((sprite, _)) = &mut self.remote_sprite {
+            *sprite = texture;
+        }
+    }
+
+    pub fn id(&self) -> Option<u16> {
+        self.id
+    }
+
+    //the server's world seed, known once it welcomed us
+    pub fn seed(&self) -> Option<u64> {
+        self.world.map(|(seed, _)| seed)
+    }
+
+    //the server's world size in chunks, known once it welcomed us
+    pub fn area_size(&self) -> Option<usize> {
+        self.world.map(|(_, area_size)| area_size)
+    }
+
+    pub fn pending_inputs(&self) -> usize {
+        self.pending.len()
+    }
+
+    //call after every fixed tick at TICK_RATE, keeps saying hello until the server answers and
+    //then sends the intent the local player was just moved with, the server replays it
+    pub fn tick(&mut self, world: &World, local: Entity) -> io::Result<()> {
+        if self.id.is_none() {
+            if self.last_hello.elapsed() >= HELLO_INTERVAL {
+                self.last_hello = Instant::now();
+                self.socket.send(&self.hello)?;
+            }
+            return Ok(());
+        }
+        let Some(intent) = world.intents.get(local) else {
+            return Ok(());
+        };
+        self.seq = self.seq.wrapping_add(1);
+        let command = InputCommand {
+            seq: self.seq,
+            movement: intent.movement,
+            desired_rotation: intent.desired_rotation,
+        };
+        self.pending.push_back(command);
+        if self.pending.len() > MAX_PENDING_INPUTS {
+            self.pending.pop_front();
+        }
+        let resend = self.pending.len().saturating_sub(MAX_RESENT_INPUTS);
+        let message = ClientMessage::Input {
+            commands: self.pending.iter().skip(resend).copied().collect(),
+        };
+        self.socket.send(&message.encode())?;
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self) -> io::Result<()> {
+        self.socket.send(&ClientMessage::Bye.encode())?;
+        Ok(())
+    }
+
+    //applies everything the server sent since the last call to the world,
+    //`local` is the entity of this client's player and `terrain` the ground it walks on
+    pub fn poll(&mut self, world: &mut World, terrain: &Terrain, local: Entity) -> io::Result<()> {
+        let mut messages = Vec::new();
+        receive_all(&self.socket, |_, bytes| messages.extend(ServerMessage::decode(bytes)))?;
+        for message in messages {
+            match message {
+                ServerMessage::Welcome { id, seed, area_size } => {
+                    self.id = Some(id);
+                    self.world = Some((seed, area_size as usize));
+                }
+                ServerMessage::Snapshot { tick, part, parts, players } => {
+                    //udp can reorder, only the newest snapshot counts
+                    if self.id.is_none() || self.last_tick.is_some_and(|last| tick <= last) {
+                        continue;
+                    }
+                    //a snapshot is applied once all of its parts are here, one with a part lost
+                    //is dropped when the next one starts
+                    if self.partial.as_ref().is_some_and(|partial| partial.tick > tick) {
+                        continue;
+                    }
+                    if self.partial.as_ref().is_none_or(|partial| partial.tick != tick) {
+                        self.partial = Some(PartialSnapshot {
+                            tick,
+                            received: vec![false; parts as usize],
+                            players: Vec::new(),
+                        });
+                    }
+                    let partial = self.partial.as_mut().unwrap();
+                    match partial.received.get_mut(part as usize) {
+                        Some(received) if !*received => {
+                            *received = true;
+                        }
+                        _ => {
+                            continue;
+                        }
+                    }
+                    partial.players.extend(players);
+                    if partial.received.iter().all(|received| *received) {
+                        let partial = self.partial.take().unwrap();
+                        self.last_tick = Some(tick);
+                        self.apply_snapshot(world, terrain, local, &partial.players);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_snapshot(
+        &mut self,
+        world: &mut World,
+        terrain: &Terrain,
+        local: Entity,
+        players: &[PlayerSnapshot]
+    ) {
+        let now = Instant::now();
+        for player in players {
+            if Some(player.id) == self.id {
+                self.reconcile(world, terrain, local, player);
+                continue;
+            }
+            let sprite = &self.remote_sprite;
+            let remote = self.remotes.entry(player.id).or_insert_with(|| {
+                let mut builder = PlayerBuilder::new()
+                    .id(player.id)
+                    .display_name(&player.display_name)
+                    .color(Color::new(player.color[0], player.color[1], player.color[2], 1.0));
+                if let Some((texture, sheet)) = sprite {
+                    builder = builder.sprite(texture.clone(), *sheet);
+                }
+                Remote {
+                    entity: builder.spawn_remote(world),
+                    samples: VecDeque::new(),
+                }
+            });
+            remote.samples.push_back(Sample {
+                time: now,
+                pos: Vec2::new(player.x, player.y),
+                velocity: Vec2::new(player.vx, player.vy),
+                degrees: player.degrees,
+            });
+        }
+        self.remotes.retain(|id, remote| {
+            let present = players.iter().any(|player| player.id == *id);
+            if !present {
+                world.despawn(remote.entity);
+            }
+            present
+        });
+    }
+
+    //rewinds the local player to the server state and replays the inputs it has not seen yet
+    fn reconcile(&mut self, world: &mut World, terrain: &Terrain, local: Entity, player: &PlayerSnapshot) {
+        while self.pending.front().is_some_and(|command| command.seq <= player.last_input) {
+            self.pending.pop_front();
+        }
+        if let Some(transform) = world.transforms.get_mut(local) {
+            transform.mut_position().set(player.x, player.y);
+            transform.mut_rotation().reset(Angle::from_degrees(player.degrees));
+            transform.set_velocity(Vec2::new(player.vx, player.vy));
+            self.pending.iter().for_each(|command| command.apply(transform, terrain));
+        }
+    }
+
+    //moves remote players to where they were INTERPOLATION_DELAY ago, blending two snapshots
+    pub fn interpolate(&mut self, world: &mut World) {
+        let Some(render_time) = Instant::now().checked_sub(INTERPOLATION_DELAY) else {
+            return;
+        };
+        for remote in self.remotes.values_mut() {
+            while remote.samples.len() > 2 && remote.samples[1].time <= render_time {
+                remote.samples.pop_front();
+            }
+            let (pos, velocity, degrees) = match (remote.samples.front(), remote.samples.get(1)) {
+                (Some(a), Some(b)) if b.time > a.time && render_time > a.time => {
+                    let t = ((render_time - a.time).as_secs_f32() / (b.time - a.time).as_secs_f32()).min(1.0);
+                    let degrees = Angle::from_degrees(a.degrees).lerp(Angle::from_degrees(b.degrees), t);
+                    (a.pos.lerp(b.pos, t), a.velocity.lerp(b.velocity, t), degrees.degrees())
+                }
+                (Some(a), _) => (a.pos, a.velocity, a.degrees),
+                _ => {
+                    continue;
+                }
+            };
+            if let Some(transform) = world.transforms.get_mut(remote.entity) {
+                transform.mut_position().reset(pos.x, pos.y);
+                transform.mut_rotation().reset(Angle::from_degrees(degrees));
+                //only drives the walk animation, remote players are not simulated
+                transform.set_velocity(velocity);
+            }
+        }
+    }
+}
+
+struct Delayed {
+    due: Instant,
+    from: SocketAddr,
+    to_server: bool,
+    bytes: Vec<u8>,
+}
+
+//udp relay that delays, jitters and drops packets between clients and a server,
+//clients connect to the proxy address instead of the server's
+pub struct LatencyProxy {
+    socket: UdpSocket,
+    server: SocketAddr,
+    //one upstream socket per client so the server sees them as separate peers
+    links: HashMap<SocketAddr, UdpSocket>,
+    queue: Vec<Delayed>,
+    latency: Duration,
+    jitter: Duration,
+    loss: f32,
+}
+
+impl LatencyProxy {
+    //`latency` is one way, each packet gets up to `jitter` more and is dropped with chance `loss`
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        server: SocketAddr,
+        latency: Duration,
+        jitter: Duration,
+        loss: f32
+    ) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(LatencyProxy {
+            socket,
+            server,
+            links: HashMap::new(),
+            queue: Vec::new(),
+            latency,
+            jitter,
+            loss,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    //forwards everything that is due, call it as often as possible
+    pub fn pump(&mut self) -> io::Result<()> {
+        let mut received = Vec::new();
+        receive_all(&self.socket, |from, bytes| received.push((from, true, bytes.to_vec())))?;
+        for (client, link) in &self.links {
+            receive_all(link, |_, bytes| received.push((*client, false, bytes.to_vec())))?;
+        }
+        let now = Instant::now();
+        for (from, to_server, bytes) in received {
+            if random::<f32>() < self.loss {
+                continue;
+            }
+            let due = now + self.latency + self.jitter.mul_f32(random::<f32>());
+            self.queue.push(Delayed { due, from, to_server, bytes });
+        }
+
+        let (due, waiting): (Vec<Delayed>, Vec<Delayed>) = self.queue
+            .drain(..)
+            .partition(|packet| packet.due <= now);
+        self.queue = waiting;
+        for packet in due {
+            if packet.to_server {
+                if !self.links.contains_key(&packet.from) {
+                    let link = UdpSocket::bind("127.0.0.1:0")?;
+                    link.connect(self.server)?;
+                    link.set_nonblocking(true)?;
+                    self.links.insert(packet.from, link);
+                }
+                self.links[&packet.from].send(&packet.bytes)?;
+            } else {
+                self.socket.send_to(&packet.bytes, packet.from)?;
+            }
+        }
+        Ok(())
+    }
+}
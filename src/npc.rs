@@ -0,0 +1,127 @@
+use crate::*;
+use notan::random::rand::Rng;
+use notan::random::utils::Random;
+
+const WANDER_RADIUS: f32 = 96.0;
+const WANDER_WAIT: f32 = 2.0;
+pub const SENSE_RADIUS: f32 = 128.0;
+
+pub enum Behaviour {
+    Wander {
+        home: Vec2,
+        target: Option<Vec2>,
+        wait: f32,
+    },
+    Patrol {
+        waypoints: Vec<Vec2>,
+        next: usize,
+    },
+    //keeps `distance` away from the target
+    Follow {
+        distance: f32,
+    },
+    //runs from the target while it is closer than `distance`
+    Flee {
+        distance: f32,
+    },
+}
+
+impl Behaviour {
+    pub fn wander(home: Vec2) -> Self {
+        Behaviour::Wander { home, target: None, wait: 0.0 }
+    }
+
+    pub fn patrol(waypoints: Vec<Vec2>) -> Self {
+        Behaviour::Patrol { waypoints, next: 0 }
+    }
+}
+
+pub struct NpcBuilder {
+    behaviour: Behaviour,
+    color: Color,
+    transform: TransformPR,
+}
+
+impl NpcBuilder {
+    pub fn new(behaviour: Behaviour) -> Self {
+        NpcBuilder {
+            behaviour,
+            color: Color::GREEN,
+            transform: TransformPR::new().with_movement_speed(30.0),
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> NpcBuilder {
+        self.transform = self.transform.with_position(x, y);
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> NpcBuilder {
+        self.color = color;
+        self
+    }
+
+    pub fn spawn(self, world: &mut World) -> Entity {
+        let entity = world.spawn();
+        world.transforms.insert(entity, self.transform);
+        world.intents.insert(entity, Intent::default());
+        world.renderables.insert(entity, Renderable::Circle { color: self.color });
+        world.colliders.insert(entity, Collider { radius: 5.0 });
+        world.behaviours.insert(entity, self.behaviour);
+        entity
+    }
+}
+
+impl Behaviour {
+    //returns the movement input for this tick and points `desired_rotation` where to go,
+    //`target` is the closest player within SENSE_RADIUS, `rng` is the simulation's so replays match
+    pub fn think(
+        &mut self,
+        transform: &TransformPR,
+        desired_rotation: &mut Angle,
+        delta: f32,
+        target: Option<Vec2>,
+        rng: &mut Random
+    ) -> Vec2 {
+        let pos = transform.position().vec();
+        let goal = match self {
+            Behaviour::Wander { home, target: wander_target, wait } => {
+                if let Some(goal) = *wander_target {
+                    if transform.reached(goal) {
+                        *wander_target = None;
+                        *wait = WANDER_WAIT * rng.gen::<f32>();
+                    }
+                } else if *wait > 0.0 {
+                    *wait -= delta;
+                } else {
+                    let offset = Vec2::from_angle(rng.gen::<f32>() * std::f32::consts::TAU);
+                    *wander_target = Some(*home + offset * WANDER_RADIUS * rng.gen::<f32>());
+                }
+                *wander_target
+            }
+            Behaviour::Patrol { waypoints, next } => {
+                if waypoints.is_empty() {
+                    None
+                } else {
+                    if transform.reached(waypoints[*next]) {
+                        *next = (*next + 1) % waypoints.len();
+                    }
+                    Some(waypoints[*next])
+                }
+            }
+            Behaviour::Follow { distance } => {
+                target.filter(|target| pos.distance(*target) > *distance)
+            }
+            Behaviour::Flee { distance } => {
+                target
+                    .map(|target| pos - target)
+                    .filter(|away| away.length() < *distance)
+                    .map(|away| pos + away.normalize_or_zero() * *distance)
+            }
+        };
+        match goal {
+            Some(goal) => transform.seek(desired_rotation, goal),
+            None => Vec2::new(0.0, 0.0),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::cmp::Ordering;
+use std::collections::{ BinaryHeap, HashMap, HashSet };
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+//tiles expanded before giving up, a goal walled off from the start would otherwise make the
+//search visit every tile it can reach
+pub const MAX_EXPANDED_TILES: usize = 50_000;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct Node {
+    cost: u32,
+    tile: (usize, usize),
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost).then_with(|| self.tile.cmp(&other.tile))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn heuristic(a: (usize, usize), b: (usize, usize)) -> u32 {
+    let dx = a.0.abs_diff(b.0) as u32;
+    let dy = a.1.abs_diff(b.1) as u32;
+    STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+}
+
+//a* over a grid of `size` tiles, diagonal steps need both tiles next to them free so paths
+//never cut wall corners. the tiles to walk through without `start`, None when there is no path
+//within MAX_EXPANDED_TILES
+pub fn find_path(
+    start: (usize, usize),
+    goal: (usize, usize),
+    size: (usize, usize),
+    is_blocked: impl Fn((usize, usize)) -> bool
+) -> Option<Vec<(usize, usize)>> {
+    if goal.0 >= size.0 || goal.1 >= size.1 || start.0 >= size.0 || start.1 >= size.1 || is_blocked(goal) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from = HashMap::<(usize, usize), (usize, usize)>::new();
+    let mut cost_so_far = HashMap::<(usize, usize), u32>::new();
+    //a tile is pushed again when a cheaper way to it is found, the older entries are skipped
+    let mut closed = HashSet::<(usize, usize)>::new();
+    open.push(Node { cost: heuristic(start, goal), tile: start });
+    cost_so_far.insert(start, 0);
+
+    let mut expanded = 0;
+    while let Some(Node { tile, .. }) = open.pop() {
+        if !closed.insert(tile) {
+            continue;
+        }
+        expanded += 1;
+        if expanded > MAX_EXPANDED_TILES {
+            return None;
+        }
+        if tile == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                if previous == start {
+                    break;
+                }
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let cost = cost_so_far[&tile];
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)] {
+            let nx = (tile.0 as i64) + dx;
+            let ny = (tile.1 as i64) + dy;
+            if nx < 0 || ny < 0 || (nx as usize) >= size.0 || (ny as usize) >= size.1 {
+                continue;
+            }
+            let next = (nx as usize, ny as usize);
+            if is_blocked(next) {
+                continue;
+            }
+            let step = if dx != 0 && dy != 0 {
+                if is_blocked((next.0, tile.1)) || is_blocked((tile.0, next.1)) {
+                    continue;
+                }
+                DIAGONAL_COST
+            } else {
+                STRAIGHT_COST
+            };
+            let new_cost = cost + step;
+            if cost_so_far.get(&next).is_none_or(|&c| new_cost < c) {
+                cost_so_far.insert(next, new_cost);
+                came_from.insert(next, tile);
+                open.push(Node { cost: new_cost + heuristic(next, goal), tile: next });
+            }
+        }
+    }
+    None
+}
@@ -4,115 +4,139 @@ use notan::random::rand;
 pub struct Player {
     id: u16,
     display_name: String,
-    desired_rotation: Rotation,
-    color: Color,
-    transform: TransformPR,
     last_coords: (usize, usize),
 }
 
 impl std::fmt::Debug for Player {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "Player{}({},{})",
-            self.id,
-            self.transform.position().x(),
-            self.transform.position().y()
-        )
+        write!(f, "Player{}({})", self.id, self.display_name)
     }
 }
 
 impl Player {
-    pub fn speed_max_xy(&self) -> f32 {
-        self.transform.position().speed_max_xy()
-    }
-    pub fn pos(&self) -> &Position {
-        self.transform.position()
+    pub fn id(&self) -> u16 {
+        self.id
     }
-    fn pos_touple(&self) -> (f32, f32) {
-        self.transform.pos_touple()
+    pub fn display_name(&self) -> &str {
+        &self.display_name
     }
     pub fn last_coords(&self) -> (usize, usize) {
         self.last_coords
     }
 
-    pub fn pos_mut(&mut self) -> &mut Position {
-        self.transform.mut_position()
-    }
-
-    pub fn render(&self, draw: &mut Draw) {
-        let (x, y) = self.pos_touple();
-        draw.circle(5.0).position(x, y).color(Color::new(0.1, 0.1, 0.1, 1.0));
-        draw.circle(4.0).position(x, y).color(self.color);
-        draw.line((x, y), (x, y + 5.0))
-            .rotate_degrees_from((x, y), self.transform.rotation().degrees_normalized() - 90.0)
-            .color(Color::new(0.1, 0.1, 0.1, 0.7));
-    }
-
-    pub fn color_rgb(&self) -> [f32; 3] {
-        self.color.rgb()
-    }
-
-    pub fn set_color(&mut self, r: f32, g: f32, b: f32) {
-        self.color = Color::new(r, g, b, 1.0);
-    }
-
-    pub fn rotation(&self) -> &Rotation {
-        &self.transform.rotation()
-    }
-    pub fn desired_rotation(&self) -> &Rotation {
-        &self.desired_rotation
-    }
-    pub fn set_desired_rotation(&mut self, degrees: f32) {
-        self.desired_rotation.set_smooth(degrees);
-    }
-
-    pub fn update(&mut self, app: &mut App) -> bool {
-        self.transform.update(
-            app,
-            Player::get_player_input(app),
-            self.desired_rotation.smooth_degrees()
-        );
-        self.check_chunk_change()
-    }
-
-    fn check_chunk_change(&mut self) -> bool {
-        let coords = Chunk::pos_to_coords(self.pos().vec());
-        if self.last_coords != coords && self.speed_max_xy() < Chunk::size() as f32 / 4.0 {
+    //true when the player entered another chunk, not while flying faster than a quarter chunk a
+    //tick, `delta` is the length of a tick
+    pub fn check_chunk_change(&mut self, transform: &TransformPR, delta: f32) -> bool {
+        let coords = Chunk::pos_to_coords(transform.position().vec());
+        let per_tick = transform.speed() * delta;
+        if self.last_coords != coords && per_tick < Chunk::size() as f32 / 4.0 {
             self.last_coords = coords;
             return true
         }
         false
     }
 
-    fn get_player_input(app: &App) -> Vec2 {
+    pub fn get_player_input(app: &App, bindings: &KeyBindings) -> Vec2 {
         let mut pos: Vec2 = Vec2::new(0.0, 0.0);
 
-        if app.keyboard.is_down(KeyCode::W) {
+        if bindings.is_down(&app.keyboard, Action::MoveForward) {
             pos.y -= 1.0;
         }
 
-        if app.keyboard.is_down(KeyCode::A) {
-            pos.x -= 0.75;
+        if bindings.is_down(&app.keyboard, Action::StrafeLeft) {
+            pos.x -= 1.0;
         }
 
-        if app.keyboard.is_down(KeyCode::S) {
-            pos.y += 0.5;
+        if bindings.is_down(&app.keyboard, Action::MoveBack) {
+            pos.y += 1.0;
         }
 
-        if app.keyboard.is_down(KeyCode::D) {
-            pos.x += 0.75;
+        if bindings.is_down(&app.keyboard, Action::StrafeRight) {
+            pos.x += 1.0;
         }
         pos
     }
 }
 
+//what the directions of the movement input mean
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum MovementModel {
+    //up walks where the player faces, left and right strafe around it
+    #[default]
+    FacingRelative,
+    //up walks up the screen whichever way the player faces, twin-stick style
+    ScreenRelative,
+}
+
+impl MovementModel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MovementModel::FacingRelative => "facing",
+            MovementModel::ScreenRelative => "screen",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MovementModel> {
+        match name {
+            "facing" => Some(MovementModel::FacingRelative),
+            "screen" => Some(MovementModel::ScreenRelative),
+            _ => None,
+        }
+    }
+
+    //turns the input into the facing-relative vector TransformPR::update takes,
+    //so the server and replays never need to know which model was used
+    pub fn to_local(&self, movement: Vec2, desired_rotation: Angle) -> Vec2 {
+        match self {
+            MovementModel::FacingRelative => movement,
+            MovementModel::ScreenRelative =>
+                Vec2::from_angle(-(desired_rotation + 90.0).radians()).rotate(movement),
+        }
+    }
+}
+
+//marks an entity as driven by keyboard and mouse, holds the click-to-move path
+#[derive(Default)]
+pub struct PlayerInput {
+    //waypoints in reverse order, the next one is last
+    path: Vec<Vec2>,
+    pub model: MovementModel,
+}
+
+impl PlayerInput {
+    pub fn has_path(&self) -> bool {
+        !self.path.is_empty()
+    }
+    pub fn set_path(&mut self, mut waypoints: Vec<Vec2>) {
+        waypoints.reverse();
+        self.path = waypoints;
+    }
+    pub fn clear_path(&mut self) {
+        self.path.clear();
+    }
+
+    pub fn follow_path(&mut self, transform: &TransformPR, desired_rotation: &mut Angle) -> Vec2 {
+        while let Some(waypoint) = self.path.last() {
+            if !transform.reached(*waypoint) {
+                break;
+            }
+            self.path.pop();
+        }
+        match self.path.last() {
+            Some(waypoint) => transform.seek(desired_rotation, *waypoint),
+            None => Vec2::new(0.0, 0.0),
+        }
+    }
+}
+
 pub struct PlayerBuilder {
     id: u16,
     display_name: String,
-    desired_rotation: Rotation,
+    desired_rotation: Angle,
     color: Color,
     transform: TransformPR,
+    model: MovementModel,
+    sprite: Option<(Texture, SpriteSheet)>,
 }
 
 impl Default for PlayerBuilder {
@@ -120,9 +144,11 @@ impl Default for PlayerBuilder {
         PlayerBuilder {
             id: 0,
             display_name: "Blank".to_string(),
-            desired_rotation: Rotation::zero(),
+            desired_rotation: Angle::ZERO,
             color: Color::BLUE,
             transform: TransformPR::new(),
+            model: MovementModel::default(),
+            sprite: None,
         }
     }
 }
@@ -132,24 +158,98 @@ impl PlayerBuilder {
         PlayerBuilder::default()
     }
 
+    pub fn id(mut self, id: u16) -> PlayerBuilder {
+        self.id = id;
+        self
+    }
+
+    pub fn display_name(mut self, display_name: &str) -> PlayerBuilder {
+        self.display_name = display_name.to_string();
+        self
+    }
+
     pub fn color(mut self, color: Color) -> PlayerBuilder {
         self.color = color;
         self
     }
 
+    //draws the player from an animated sprite sheet tinted with its color instead of a circle
+    pub fn sprite(mut self, texture: Texture, sheet: SpriteSheet) -> PlayerBuilder {
+        self.sprite = Some((texture, sheet));
+        self
+    }
+
+    pub fn movement_model(mut self, model: MovementModel) -> PlayerBuilder {
+        self.model = model;
+        self
+    }
+
+    pub fn direction_speeds(mut self, direction_speeds: DirectionSpeeds) -> PlayerBuilder {
+        self.transform = self.transform.with_direction_speeds(direction_speeds);
+        self
+    }
+
+    //degrees per second and how the turn slows down close to the target
+    pub fn turning(mut self, rotation_speed: f32, turn_easing: TurnEasing) -> PlayerBuilder {
+        self.transform = self.transform.with_turning(rotation_speed, turn_easing);
+        self
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> PlayerBuilder {
+        self.transform = self.transform.with_position(x, y);
+        self
+    }
+
     pub fn color_random(mut self) -> PlayerBuilder {
         self.color = Color::new(rand::random(), rand::random(), rand::random(), 1.0);
         self
     }
 
-    pub fn build(self) -> Player {
-        Player {
+    fn renderable(&self) -> Renderable {
+        match &self.sprite {
+            Some((texture, sheet)) =>
+                Renderable::Animated {
+                    texture: texture.clone(),
+                    sheet: *sheet,
+                    animation: Animation::default(),
+                    color: self.color,
+                    size: 14.0,
+                },
+            None => Renderable::Circle { color: self.color },
+        }
+    }
+
+    pub fn spawn(self, world: &mut World) -> Entity {
+        let renderable = self.renderable();
+        let entity = world.spawn();
+        world.players.insert(entity, Player {
             id: self.id,
             display_name: self.display_name,
+            last_coords: (1, 1),
+        });
+        world.transforms.insert(entity, self.transform);
+        world.intents.insert(entity, Intent {
+            movement: Vec2::new(0.0, 0.0),
             desired_rotation: self.desired_rotation,
-            color: self.color,
-            transform: self.transform,
+        });
+        world.renderables.insert(entity, renderable);
+        world.inputs.insert(entity, PlayerInput { path: Vec::new(), model: self.model });
+        world.colliders.insert(entity, Collider { radius: 5.0 });
+        entity
+    }
+
+    //another client's player, moved only by server snapshots
+    pub fn spawn_remote(self, world: &mut World) -> Entity {
+        let renderable = self.renderable();
+        let entity = world.spawn();
+        world.networked.insert(entity, Networked { id: self.id });
+        world.players.insert(entity, Player {
+            id: self.id,
+            display_name: self.display_name,
             last_coords: (1, 1),
-        }
+        });
+        world.transforms.insert(entity, self.transform);
+        world.renderables.insert(entity, renderable);
+        entity
     }
 }
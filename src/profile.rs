@@ -0,0 +1,202 @@
+use crate::*;
+use notan::random::rand;
+use std::io;
+use std::path::{ Path, PathBuf };
+
+pub const DEFAULT_PROFILES_DIR: &str = "profiles";
+const EXTENSION: &str = "profile";
+
+//who is playing, one `<name>.profile` file per player in the profiles directory
+#[derive(Clone, PartialEq, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub color: Color,
+    //the world the player was last in and where, both None until the first offline session
+    pub seed: Option<u64>,
+    pub position: Option<Vec2>,
+    pub facing: Facing,
+    //None until the first save, then the configured zoom is used
+    pub zoom: Option<u8>,
+}
+
+impl Profile {
+    //a fresh profile with a random colour, in whole 1/255 steps so it saves without change
+    pub fn new(name: &str) -> Self {
+        let channel = || (rand::random::<u8>() as f32) / 255.0;
+        Profile {
+            name: name.to_string(),
+            color: Color::new(channel(), channel(), channel(), 1.0),
+            seed: None,
+            position: None,
+            facing: Facing::default(),
+            zoom: None,
+        }
+    }
+
+    //the file name for `name`, anything but letters, digits, `-` and `_` becomes `_`
+    pub fn file_name(name: &str) -> String {
+        let stem: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        format!("{}.{}", stem, EXTENSION)
+    }
+
+    pub fn path(dir: impl AsRef<Path>, name: &str) -> PathBuf {
+        dir.as_ref().join(Profile::file_name(name))
+    }
+
+    //one `setting = value` per line, lines starting with `#` are comments since colours and
+    //names can hold one too, only the name is required
+    pub fn parse(text: &str) -> Result<Profile, String> {
+        let mut name = None;
+        let mut parsed = Profile::new("");
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (setting, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `setting = value`", i + 1))?;
+            let value = value.trim();
+            let invalid = |what: &str| format!("line {}: `{}` is not {}", i + 1, value, what);
+            match setting.trim() {
+                "name" if !value.is_empty() => {
+                    name = Some(value.to_string());
+                }
+                "name" => {
+                    return Err(format!("line {}: the name is empty", i + 1));
+                }
+                "color" => {
+                    parsed.color = parse_color(value).ok_or_else(|| invalid("a colour like #3080ff"))?;
+                }
+                "seed" => {
+                    parsed.seed = Some(value.parse().map_err(|_| invalid("a seed"))?);
+                }
+                "position" => {
+                    let position = value
+                        .split_once(' ')
+                        .and_then(|(x, y)| Some(Vec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                        .filter(|position| position.is_finite())
+                        .ok_or_else(|| invalid("a position like `2048 2048`"))?;
+                    parsed.position = Some(position);
+                }
+                "facing" => {
+                    parsed.facing = Facing::from_name(value).ok_or_else(|| invalid("`four`, `eight` or `free`"))?;
+                }
+                "zoom" => {
+                    parsed.zoom = Some(value.parse().map_err(|_| invalid("a zoom level"))?);
+                }
+                other => {
+                    return Err(format!("line {}: unknown setting `{}`", i + 1, other));
+                }
+            }
+        }
+        parsed.name = name.ok_or("the profile has no name")?;
+        Ok(parsed)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# player profile, written by the game on exit\n");
+        text.push_str(&format!("name = {}\n", self.name));
+        text.push_str(&format!("color = {}\n", color_to_hex(self.color)));
+        if let Some(seed) = self.seed {
+            text.push_str(&format!("seed = {}\n", seed));
+        }
+        if let Some(position) = self.position {
+            text.push_str(&format!("position = {} {}\n", position.x, position.y));
+        }
+        text.push_str(&format!("facing = {}\n", self.facing.name()));
+        if let Some(zoom) = self.zoom {
+            text.push_str(&format!("zoom = {}\n", zoom));
+        }
+        text
+    }
+
+    //None when there is no profile with that name yet
+    pub fn load(dir: impl AsRef<Path>, name: &str) -> io::Result<Option<Profile>> {
+        Profile::load_file(Profile::path(dir, name))
+    }
+
+    fn load_file(path: impl AsRef<Path>) -> io::Result<Option<Profile>> {
+        match std::fs::read_to_string(path) {
+            Ok(text) =>
+                Profile::parse(&text)
+                    .map(Some)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(Profile::path(dir, &self.name), self.to_text())
+    }
+
+    //names of every profile in `dir`, a missing directory has none
+    pub fn list(dir: impl AsRef<Path>) -> io::Result<Vec<String>> {
+        let mut names: Vec<String> = Profile::files(dir)?
+            .into_iter()
+            .filter_map(|(path, _)| Profile::load_file(path).ok().flatten())
+            .map(|profile| profile.name)
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    //the profile saved most recently, which is the one played last
+    pub fn last_played(dir: impl AsRef<Path>) -> io::Result<Option<Profile>> {
+        let mut files = Profile::files(dir)?;
+        files.sort_by_key(|(_, modified)| *modified);
+        match files.pop() {
+            Some((path, _)) => Profile::load_file(path),
+            None => Ok(None),
+        }
+    }
+
+    //the profile called `name`, or the last played one without a name,
+    //a new profile is made when there is none
+    pub fn choose(dir: impl AsRef<Path>, name: Option<&str>) -> io::Result<Profile> {
+        let found = match name {
+            Some(name) => Profile::load(&dir, name)?,
+            None => Profile::last_played(&dir)?,
+        };
+        Ok(found.unwrap_or_else(|| Profile::new(name.unwrap_or("Blank"))))
+    }
+
+    fn files(dir: impl AsRef<Path>) -> io::Result<Vec<(PathBuf, std::time::SystemTime)>> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == EXTENSION) {
+                files.push((path.clone(), std::fs::metadata(&path)?.modified()?));
+            }
+        }
+        Ok(files)
+    }
+}
+
+fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok().map(|c| (c as f32) / 255.0);
+    Some(Color::new(channel(0)?, channel(2)?, channel(4)?, 1.0))
+}
+
+fn color_to_hex(color: Color) -> String {
+    let [r, g, b] = color.rgb().map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
@@ -0,0 +1,101 @@
+use crate::*;
+
+//in-game list of actions and their keys, the arrow keys pick an action, enter waits for a key
+//to add, delete clears it, tab switches the movement model and escape closes the screen
+//or stops waiting
+#[derive(Default)]
+pub struct RebindScreen {
+    open: bool,
+    selected: usize,
+    capturing: bool,
+}
+
+impl RebindScreen {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.capturing = false;
+    }
+
+    //returns true when the screen was closed this frame, so the bindings can be saved
+    pub fn update(&mut self, app: &App, bindings: &mut KeyBindings) -> bool {
+        let keyboard = &app.keyboard;
+        let action = Action::ALL[self.selected];
+        if self.capturing {
+            if keyboard.was_pressed(KeyCode::Escape) {
+                self.capturing = false;
+            } else if let Some(binding) = pressed_binding(keyboard) {
+                bindings.bind(action, binding);
+                self.capturing = false;
+            }
+            return false;
+        }
+        if keyboard.was_pressed(KeyCode::Escape) || bindings.was_pressed(keyboard, Action::OpenBindings) {
+            self.open = false;
+            return true;
+        }
+        if keyboard.was_pressed(KeyCode::Up) {
+            self.selected = (self.selected + Action::ALL.len() - 1) % Action::ALL.len();
+        }
+        if keyboard.was_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % Action::ALL.len();
+        }
+        if keyboard.was_pressed(KeyCode::Return) {
+            self.capturing = true;
+        }
+        if keyboard.was_pressed(KeyCode::Delete) || keyboard.was_pressed(KeyCode::Back) {
+            bindings.clear(action);
+        }
+        if keyboard.was_pressed(KeyCode::Tab) {
+            bindings.movement_model = match bindings.movement_model {
+                MovementModel::FacingRelative => MovementModel::ScreenRelative,
+                MovementModel::ScreenRelative => MovementModel::FacingRelative,
+            };
+        }
+        false
+    }
+
+    pub fn render(&self, draw: &mut Draw, font: &Font, bindings: &KeyBindings) {
+        let (width, height) = draw.size();
+        draw.rect((0.0, 0.0), (width, height)).color(Color::new(0.0, 0.0, 0.0, 0.75));
+        draw.text(font, "Key bindings")
+            .position(40.0, 40.0)
+            .size(24.0);
+        draw.text(font, "up/down select, enter add key, delete clear, tab movement model, escape close")
+            .position(40.0, 72.0)
+            .size(14.0)
+            .color(Color::GRAY);
+        for (i, action) in Action::ALL.into_iter().enumerate() {
+            let keys = if self.capturing && i == self.selected {
+                "press a key...".to_string()
+            } else {
+                let keys: Vec<String> = bindings
+                    .get(action)
+                    .iter()
+                    .map(|binding| binding.to_string())
+                    .collect();
+                keys.join(", ")
+            };
+            let color = if i == self.selected { Color::YELLOW } else { Color::WHITE };
+            let y = 110.0 + (i as f32) * 24.0;
+            draw.text(font, action.name())
+                .position(40.0, y)
+                .size(16.0)
+                .color(color);
+            draw.text(font, &keys)
+                .position(260.0, y)
+                .size(16.0)
+                .color(color);
+        }
+        let y = 110.0 + (Action::ALL.len() as f32) * 24.0 + 12.0;
+        draw.text(font, "movement_model")
+            .position(40.0, y)
+            .size(16.0);
+        draw.text(font, bindings.movement_model.name())
+            .position(260.0, y)
+            .size(16.0);
+    }
+}
@@ -20,6 +20,23 @@ pub fn render_bg(gfx: &mut Graphics, texture: &Texture) {
     gfx.render(&draw);
 }
 
+//the background with a bar filled to `progress`, 0..1, and what is loading above it
+pub fn render_loading(gfx: &mut Graphics, background: &Texture, font: &Font, label: &str, progress: f32) {
+    render_bg(gfx, background);
+    let (width, height) = (gfx.size().0 as f32, gfx.size().1 as f32);
+    let bar_width = width * 0.6;
+    let (x, y) = ((width - bar_width) / 2.0, height * 0.8);
+    let mut draw = gfx.create_draw();
+    draw.rect((x - 2.0, y - 2.0), (bar_width + 4.0, 20.0)).color(Color::new(0.1, 0.1, 0.1, 0.8));
+    draw.rect((x, y), (bar_width * progress.clamp(0.0, 1.0), 16.0)).color(Color::WHITE);
+    draw.text(font, label)
+        .position(width / 2.0, y - 8.0)
+        .size(18.0)
+        .h_align_center()
+        .v_align_bottom();
+    gfx.render(&draw);
+}
+
 pub fn bg_projection(win_size: (u32, u32), img_bigger_side: f32) -> (Mat4, f32) {
     let win_size = Vec2::new(win_size.0 as f32, win_size.1 as f32);
     let work_size = Vec2::new(img_bigger_side, img_bigger_side);
@@ -49,3 +66,11 @@ pub fn world_projection(win_size: (u32, u32), render_size: u8) -> (Mat4, f32) {
 
     (Mat4::orthographic_rh_gl(left, right, bottom, top, near, far), aspect_ratio)
 }
+
+pub fn render_body(draw: &mut Draw, (x, y): (f32, f32), degrees: f32, color: Color) {
+    draw.circle(5.0).position(x, y).color(Color::new(0.1, 0.1, 0.1, 1.0));
+    draw.circle(4.0).position(x, y).color(color);
+    draw.line((x, y), (x, y + 5.0))
+        .rotate_degrees_from((x, y), degrees - 90.0)
+        .color(Color::new(0.1, 0.1, 0.1, 0.7));
+}
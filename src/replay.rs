@@ -0,0 +1,164 @@
+use crate::*;
+use std::io;
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"IRPL";
+const VERSION: u8 = 1;
+const TICK_SIZE: usize = 12;
+
+//the local player's intent for one tick, after keyboard, mouse and click-to-move were applied
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TickInput {
+    pub movement: Vec2,
+    pub desired_rotation: Angle,
+}
+
+impl TickInput {
+    pub fn capture(sim: &Simulation) -> Option<Self> {
+        sim.world.intents.get(sim.player()).map(|intent| TickInput {
+            movement: intent.movement,
+            desired_rotation: intent.desired_rotation,
+        })
+    }
+
+    pub fn apply(&self, sim: &mut Simulation) {
+        if let Some(intent) = sim.world.intents.get_mut(sim.player()) {
+            intent.movement = self.movement;
+            intent.desired_rotation = self.desired_rotation;
+        }
+    }
+}
+
+//everything needed to run a session again tick for tick, the world comes from the seed unless
+//it was loaded or changed before the recording started
+pub struct Replay {
+    pub seed: u64,
+    pub area_size: usize,
+    pub tick_rate: f32,
+    //where the player stood on the first tick
+    pub start: Vec2,
+    //the world when it was not the one the seed generates
+    pub terrain: Option<Terrain>,
+    pub ticks: Vec<TickInput>,
+}
+
+impl Replay {
+    pub fn new(sim: &Simulation) -> Self {
+        let generated = Terrain::generate_sized(sim.seed(), sim.terrain.area_size());
+        Replay {
+            seed: sim.seed(),
+            area_size: sim.terrain.area_size(),
+            tick_rate: sim.tick_rate(),
+            start: sim.player_pos(),
+            terrain: (sim.terrain != generated).then(|| sim.terrain.clone()),
+            ticks: Vec::new(),
+        }
+    }
+
+    //call right before every Simulation::tick
+    pub fn record(&mut self, sim: &Simulation) {
+        if let Some(input) = TickInput::capture(sim) {
+            self.ticks.push(input);
+        }
+    }
+
+    //a builder for the world this was recorded in with the player at `start`, npcs still have to
+    //be added and a player given to it has to be put at `start` too
+    pub fn simulation(&self) -> SimulationBuilder {
+        let simulation = match &self.terrain {
+            Some(terrain) => SimulationBuilder::new(self.seed).terrain(terrain.clone()),
+            None => SimulationBuilder::new(self.seed).area_size(self.area_size),
+        };
+        simulation
+            .tick_rate(self.tick_rate)
+            .player(PlayerBuilder::new().position(self.start.x, self.start.y))
+    }
+
+    //runs every recorded tick on `sim`, which has to be built the same way as the recorded one
+    pub fn play(&self, sim: &mut Simulation) {
+        for input in &self.ticks {
+            input.apply(sim);
+            sim.tick();
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let terrain = self.terrain.as_ref().map(Terrain::encode).unwrap_or_default();
+        let mut bytes = Vec::with_capacity(35 + terrain.len() + self.ticks.len() * TICK_SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(self.area_size as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.tick_rate.to_le_bytes());
+        bytes.extend_from_slice(&self.start.x.to_le_bytes());
+        bytes.extend_from_slice(&self.start.y.to_le_bytes());
+        //an empty world is the generated one
+        bytes.extend_from_slice(&(terrain.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&terrain);
+        bytes.extend_from_slice(&(self.ticks.len() as u32).to_le_bytes());
+        for input in &self.ticks {
+            bytes.extend_from_slice(&input.movement.x.to_le_bytes());
+            bytes.extend_from_slice(&input.movement.y.to_le_bytes());
+            bytes.extend_from_slice(&input.desired_rotation.degrees().to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(0..4)? != MAGIC || *bytes.get(4)? != VERSION {
+            return None;
+        }
+        let f32_at = |at: usize| Some(f32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+        let seed = u64::from_le_bytes(bytes.get(5..13)?.try_into().ok()?);
+        let area_size = u16::from_le_bytes(bytes.get(13..15)?.try_into().ok()?) as usize;
+        let header = 15;
+        //a broken or hostile file must not make a huge world or a timestep that never ticks
+        if !(1..=MAX_AREA_SIZE).contains(&area_size) {
+            return None;
+        }
+        let tick_rate = f32_at(header)?;
+        if !tick_rate.is_finite() || tick_rate <= 0.0 {
+            return None;
+        }
+        let start = Vec2::new(f32_at(header + 4)?, f32_at(header + 8)?);
+        if !start.is_finite() {
+            return None;
+        }
+        let u32_at = |at: usize| Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?) as usize);
+        let terrain_size = u32_at(header + 12)?;
+        let terrain_start = header + 16;
+        let terrain = match terrain_size {
+            0 => None,
+            _ => Some(Terrain::decode(bytes.get(terrain_start..terrain_start.checked_add(terrain_size)?)?)?),
+        };
+        if terrain.as_ref().is_some_and(|terrain| terrain.seed() != seed || terrain.area_size() != area_size) {
+            return None;
+        }
+        let count_at = terrain_start + terrain_size;
+        let count = u32_at(count_at)?;
+        let ticks_start = count_at + 4;
+        if bytes.len() != ticks_start + count * TICK_SIZE {
+            return None;
+        }
+        let ticks = (0..count)
+            .map(|i| {
+                let at = ticks_start + i * TICK_SIZE;
+                Some(TickInput {
+                    movement: Vec2::new(f32_at(at)?, f32_at(at + 4)?),
+                    desired_rotation: Angle::from_degrees(f32_at(at + 8)?),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Replay { seed, area_size, tick_rate, start, terrain, ticks })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.encode())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Replay::decode(&std::fs::read(path)?).ok_or_else(||
+            io::Error::new(io::ErrorKind::InvalidData, "not a replay file")
+        )
+    }
+}
@@ -0,0 +1,108 @@
+use crate::*;
+
+//one screen of the game kept on a SceneStack, `Context` is what all of them share
+pub trait Scene {
+    type Context;
+
+    //when the scene is put on the stack and when it is taken off, being covered by another
+    //scene is neither
+    fn enter(&mut self, _context: &mut Self::Context) {}
+    fn exit(&mut self, _context: &mut Self::Context) {}
+
+    //an overlay lets the scenes below it show, they are drawn first
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+//what a scene wants done with the stack after it handled a frame
+pub enum Transition<S> {
+    Stay,
+    Push(S),
+    Pop,
+    Replace(S),
+    //takes every scene off and starts over with this one
+    Reset(S),
+}
+
+//the top scene gets the input, the ones below keep their state until they are on top again
+pub struct SceneStack<S: Scene> {
+    scenes: Vec<S>,
+}
+
+impl<S: Scene> Default for SceneStack<S> {
+    fn default() -> Self {
+        SceneStack { scenes: Vec::new() }
+    }
+}
+
+impl<S: Scene> SceneStack<S> {
+    pub fn new() -> Self {
+        SceneStack::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    pub fn top(&self) -> Option<&S> {
+        self.scenes.last()
+    }
+
+    pub fn top_mut(&mut self) -> Option<&mut S> {
+        self.scenes.last_mut()
+    }
+
+    //bottom first
+    pub fn iter(&self) -> impl Iterator<Item = &S> {
+        self.scenes.iter()
+    }
+
+    pub fn push(&mut self, mut scene: S, context: &mut S::Context) {
+        scene.enter(context);
+        self.scenes.push(scene);
+    }
+
+    pub fn pop(&mut self, context: &mut S::Context) -> Option<S> {
+        let mut scene = self.scenes.pop()?;
+        scene.exit(context);
+        Some(scene)
+    }
+
+    //takes every scene off, the top one first
+    pub fn clear(&mut self, context: &mut S::Context) {
+        while self.pop(context).is_some() {}
+    }
+
+    pub fn apply(&mut self, transition: Transition<S>, context: &mut S::Context) {
+        match transition {
+            Transition::Stay => {}
+            Transition::Push(scene) => self.push(scene, context),
+            Transition::Pop => {
+                self.pop(context);
+            }
+            Transition::Replace(scene) => {
+                self.pop(context);
+                self.push(scene, context);
+            }
+            Transition::Reset(scene) => {
+                self.clear(context);
+                self.push(scene, context);
+            }
+        }
+    }
+
+    //the scenes that show, bottom first: the top one and every overlay between it and the
+    //first scene below that is not one
+    pub fn visible_mut(&mut self) -> &mut [S] {
+        let first = self.scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        &mut self.scenes[first..]
+    }
+}
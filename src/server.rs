@@ -0,0 +1,279 @@
+use crate::*;
+use crate::net::*;
+use std::collections::{ HashMap, VecDeque };
+use std::io;
+use std::net::{ SocketAddr, ToSocketAddrs, UdpSocket };
+use std::time::{ Duration, Instant };
+
+//inputs a client may have waiting, about a second at TICK_RATE, the oldest are dropped past it
+const MAX_QUEUED_INPUTS: usize = 32;
+//inputs a client can save up while none arrive, so ones that came late still get applied but a
+//client never moves more ticks than the server ran plus these
+const INPUT_CATCH_UP: u32 = 3;
+//sequence numbers further ahead of the last applied one are not from an honest client, it keeps
+//no more inputs than this unacknowledged
+const MAX_INPUT_LEAD: u32 = 256;
+//players on one server, hellos past it get no welcome
+pub const MAX_CLIENTS: usize = 64;
+
+struct Client {
+    entity: Entity,
+    id: u16,
+    color: [f32; 3],
+    last_seen: Instant,
+    inputs: VecDeque<InputCommand>,
+    last_input: u32,
+    //inputs that may still be applied, one more every tick up to INPUT_CATCH_UP
+    input_budget: u32,
+}
+
+//something the operator wants to hear about, the server binary prints them
+pub enum ServerEvent {
+    Joined {
+        id: u16,
+        display_name: String,
+        addr: SocketAddr,
+    },
+    //said bye or timed out
+    Left {
+        id: u16,
+    },
+    //nothing was sent to `addr` this time, it stays connected and times out if it is gone
+    SendFailed {
+        addr: SocketAddr,
+        error: io::Error,
+    },
+    //a hello while MAX_CLIENTS were playing
+    Refused {
+        addr: SocketAddr,
+    },
+}
+
+//headless authoritative server, owns the world and every connected player
+pub struct Server {
+    socket: UdpSocket,
+    world: World,
+    terrain: Terrain,
+    clients: HashMap<SocketAddr, Client>,
+    next_id: u16,
+    tick: u32,
+    events: Vec<ServerEvent>,
+}
+
+impl Server {
+    //`seed` picks the world, clients get it with their welcome
+    pub fn bind(addr: impl ToSocketAddrs, seed: u64) -> io::Result<Self> {
+        Server::bind_sized(addr, seed, AREA_SIZE)
+    }
+
+    //like bind with a world `area_size` chunks wide and high, clients take that size too
+    pub fn bind_sized(addr: impl ToSocketAddrs, seed: u64, area_size: usize) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Server {
+            socket,
+            world: World::new(),
+            terrain: Terrain::generate_sized(seed, area_size),
+            clients: HashMap::new(),
+            next_id: 1,
+            tick: 0,
+            events: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    //everything that happened since the last call
+    pub fn take_events(&mut self) -> Vec<ServerEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    //ticks until the socket can not be read anymore, `on_event` hears about each event as it happens
+    pub fn run(&mut self, mut on_event: impl FnMut(ServerEvent)) -> io::Result<()> {
+        let tick_length = Duration::from_secs_f32(1.0 / TICK_RATE);
+        let mut next_tick = Instant::now();
+        loop {
+            self.receive()?;
+            self.step();
+            self.broadcast();
+            self.take_events().into_iter().for_each(&mut on_event);
+            next_tick += tick_length;
+            let now = Instant::now();
+            if next_tick > now {
+                std::thread::sleep(next_tick - now);
+            } else {
+                next_tick = now;
+            }
+        }
+    }
+
+    pub fn receive(&mut self) -> io::Result<()> {
+        let mut messages = Vec::new();
+        receive_all(&self.socket, |addr, bytes| {
+            if let Some(message) = ClientMessage::decode(bytes) {
+                messages.push((addr, message));
+            }
+        })?;
+        for (addr, message) in messages {
+            self.handle(addr, message);
+        }
+        Ok(())
+    }
+
+    //one client that can not be sent to must not stop the server for everyone else
+    fn send(&mut self, bytes: &[u8], addr: SocketAddr) {
+        if let Err(error) = self.socket.send_to(bytes, addr) {
+            self.events.push(ServerEvent::SendFailed { addr, error });
+        }
+    }
+
+    fn handle(&mut self, addr: SocketAddr, message: ClientMessage) {
+        if let Some(client) = self.clients.get_mut(&addr) {
+            client.last_seen = Instant::now();
+        }
+        match message {
+            ClientMessage::Hello { display_name, color } => {
+                let id = match self.clients.get(&addr) {
+                    //the welcome got lost, the client is still saying hello
+                    Some(client) => client.id,
+                    None if self.clients.len() >= MAX_CLIENTS => {
+                        self.events.push(ServerEvent::Refused { addr });
+                        return;
+                    }
+                    None => self.join(addr, &display_name, color),
+                };
+                let welcome = ServerMessage::Welcome {
+                    id,
+                    seed: self.terrain.seed(),
+                    area_size: self.terrain.area_size() as u16,
+                };
+                self.send(&welcome.encode(), addr);
+            }
+            ClientMessage::Input { commands } => {
+                let Some(client) = self.clients.get_mut(&addr) else {
+                    return;
+                };
+                //inputs arrive resent and possibly out of order, keep each one once and in sequence
+                for mut command in commands {
+                    let newest = client.inputs.back().map_or(client.last_input, |c| c.seq);
+                    if command.seq <= newest || command.seq > client.last_input.saturating_add(MAX_INPUT_LEAD) {
+                        continue;
+                    }
+                    //clamping lets nan through and it would spread into the position
+                    if !command.movement.is_finite() || !command.desired_rotation.degrees().is_finite() {
+                        continue;
+                    }
+                    command.movement = command.movement.clamp(Vec2::splat(-1.0), Vec2::splat(1.0));
+                    client.inputs.push_back(command);
+                    //a dropped input is never applied, acknowledging it lets the client move on
+                    if client.inputs.len() > MAX_QUEUED_INPUTS {
+                        if let Some(dropped) = client.inputs.pop_front() {
+                            client.last_input = dropped.seq;
+                        }
+                    }
+                }
+            }
+            ClientMessage::Bye => {
+                self.leave(addr);
+            }
+        }
+    }
+
+    fn join(&mut self, addr: SocketAddr, display_name: &str, color: [f32; 3]) -> u16 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        let entity = PlayerBuilder::new()
+            .id(id)
+            .display_name(display_name)
+            .color(Color::new(color[0], color[1], color[2], 1.0))
+            .spawn(&mut self.world);
+        self.clients.insert(addr, Client {
+            entity,
+            id,
+            color,
+            last_seen: Instant::now(),
+            inputs: VecDeque::new(),
+            last_input: 0,
+            input_budget: 0,
+        });
+        self.events.push(ServerEvent::Joined { id, display_name: display_name.to_string(), addr });
+        id
+    }
+
+    fn leave(&mut self, addr: SocketAddr) {
+        if let Some(client) = self.clients.remove(&addr) {
+            self.world.despawn(client.entity);
+            self.events.push(ServerEvent::Left { id: client.id });
+        }
+    }
+
+    pub fn step(&mut self) {
+        let timed_out: Vec<SocketAddr> = self.clients
+            .iter()
+            .filter(|(_, client)| client.last_seen.elapsed() > TIMEOUT)
+            .map(|(addr, _)| *addr)
+            .collect();
+        timed_out.into_iter().for_each(|addr| self.leave(addr));
+
+        //players are only moved by their inputs, at the same fixed step the client predicts with,
+        //and never by more of them than ticks went by
+        for client in self.clients.values_mut() {
+            let Some(transform) = self.world.transforms.get_mut(client.entity) else {
+                continue;
+            };
+            client.input_budget = (client.input_budget + 1).min(INPUT_CATCH_UP);
+            while client.input_budget > 0 {
+                let Some(command) = client.inputs.pop_front() else {
+                    break;
+                };
+                command.apply(transform, &self.terrain);
+                client.last_input = command.seq;
+                client.input_budget -= 1;
+            }
+        }
+        spatial_system(&mut self.world);
+        collision_system(&mut self.world);
+        self.tick = self.tick.wrapping_add(1);
+    }
+
+    pub fn broadcast(&mut self) {
+        let players = self.clients
+            .values()
+            .filter_map(|client| {
+                let transform = self.world.transforms.get(client.entity)?;
+                let player = self.world.players.get(client.entity)?;
+                Some(PlayerSnapshot {
+                    id: client.id,
+                    display_name: player.display_name().to_string(),
+                    x: transform.position().x(),
+                    y: transform.position().y(),
+                    degrees: transform.rotation().degrees(),
+                    vx: transform.velocity().x,
+                    vy: transform.velocity().y,
+                    color: client.color,
+                    last_input: client.last_input,
+                })
+            })
+            .collect();
+        let snapshots: Vec<Vec<u8>> = ServerMessage::snapshots(self.tick, players)
+            .iter()
+            .map(ServerMessage::encode)
+            .collect();
+        let addrs: Vec<SocketAddr> = self.clients.keys().copied().collect();
+        for addr in addrs {
+            for snapshot in &snapshots {
+                self.send(snapshot, addr);
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::*;
+use notan::random::utils::Random;
+
+//everything the local player wants to do this frame, built from the keyboard and mouse
+//by the game or by hand in tests and tools
+#[derive(Clone, Copy, Default)]
+pub struct Input {
+    pub movement: Vec2,
+    //world position to face, keeps the current rotation when None
+    pub aim: Option<Vec2>,
+    //world position to walk to around walls
+    pub move_to: Option<Vec2>,
+}
+
+//the whole game without a window: terrain, entities and the fixed tick loop
+pub struct Simulation {
+    pub world: World,
+    pub terrain: Terrain,
+    player: Entity,
+    timestep: FixedTimestep,
+    //seeded from the world seed, everything random in a tick draws from here
+    rng: Random,
+}
+
+impl Simulation {
+    pub fn player(&self) -> Entity {
+        self.player
+    }
+
+    pub fn player_pos(&self) -> Vec2 {
+        self.world.transforms
+            .get(self.player)
+            .map(|transform| transform.position().vec())
+            .unwrap_or(Vec2::new(0.0, 0.0))
+    }
+
+    //where the player is drawn this frame, between the last two ticks
+    pub fn player_render_pos(&self) -> Vec2 {
+        self.world.transforms
+            .get(self.player)
+            .map(|transform| transform.interpolated(self.alpha()).0)
+            .unwrap_or(Vec2::new(0.0, 0.0))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.terrain.seed()
+    }
+
+    //swaps the terrain and random numbers for the ones of another seed, entities stay
+    pub fn reseed(&mut self, seed: u64) {
+        self.regenerate(seed, self.terrain.area_size());
+    }
+
+    //like reseed but the world can change its size too
+    pub fn regenerate(&mut self, seed: u64, area_size: usize) {
+        self.terrain = Terrain::generate_sized(seed, area_size);
+        self.rng = Random::new(seed);
+    }
+
+    //a loaded world in place of the current one, entities stay
+    pub fn set_terrain(&mut self, terrain: Terrain) {
+        self.rng = Random::new(terrain.seed());
+        self.terrain = terrain;
+    }
+
+    //puts the player somewhere without walking there, a path it was following is dropped
+    pub fn teleport_player(&mut self, pos: Vec2) {
+        if let Some(transform) = self.world.transforms.get_mut(self.player) {
+            transform.mut_position().reset(pos.x, pos.y);
+            transform.set_velocity(Vec2::new(0.0, 0.0));
+        }
+        if let Some(input) = self.world.inputs.get_mut(self.player) {
+            input.clear_path();
+        }
+    }
+
+    pub fn tick_rate(&self) -> f32 {
+        self.timestep.tick_rate()
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.timestep.alpha()
+    }
+
+    pub fn delta(&self) -> f32 {
+        self.timestep.delta()
+    }
+
+    pub fn set_tick_rate(&mut self, tick_rate: f32) {
+        self.timestep.set_tick_rate(tick_rate);
+    }
+
+    pub fn move_player_to(&mut self, target: Vec2) {
+        let path = self.terrain.find_path(self.player_pos(), target);
+        if let Some(input) = self.world.inputs.get_mut(self.player) {
+            match path {
+                Some(path) => input.set_path(path),
+                None => input.clear_path(),
+            }
+        }
+    }
+
+    pub fn apply_input(&mut self, input: &Input) {
+        if let Some(target) = input.move_to {
+            self.move_player_to(target);
+        }
+        input_system(&mut self.world, input);
+    }
+
+    //adds the frame time and returns how many ticks to run now, for callers that
+    //need to do something between ticks, everyone else uses update
+    pub fn advance(&mut self, frame_delta: f32) -> u32 {
+        self.timestep.advance(frame_delta)
+    }
+
+    //applies the input and runs as many ticks as `frame_delta` covers
+    pub fn update(&mut self, input: &Input, frame_delta: f32) -> u32 {
+        self.apply_input(input);
+        let ticks = self.advance(frame_delta);
+        for _ in 0..ticks {
+            self.tick();
+        }
+        ticks
+    }
+
+    pub fn tick(&mut self) {
+        let delta = self.timestep.delta();
+        begin_tick_system(&mut self.world);
+        ai_system(&mut self.world, delta, &mut self.rng);
+        movement_system(&mut self.world, &self.terrain, delta);
+        spatial_system(&mut self.world);
+        collision_system(&mut self.world);
+    }
+}
+
+pub struct SimulationBuilder {
+    seed: u64,
+    area_size: usize,
+    //a world loaded from disk instead of generated from the seed
+    terrain: Option<Terrain>,
+    tick_rate: f32,
+    npcs: Vec<NpcBuilder>,
+    player: PlayerBuilder,
+}
+
+impl SimulationBuilder {
+    pub fn new(seed: u64) -> Self {
+        SimulationBuilder {
+            seed,
+            area_size: AREA_SIZE,
+            terrain: None,
+            tick_rate: DEFAULT_TICK_RATE,
+            npcs: Vec::new(),
+            player: PlayerBuilder::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn tick_rate(mut self, tick_rate: f32) -> SimulationBuilder {
+        self.tick_rate = tick_rate;
+        self
+    }
+
+    //width and height of the world in chunks
+    pub fn area_size(mut self, area_size: usize) -> SimulationBuilder {
+        self.area_size = area_size;
+        self
+    }
+
+    //plays on `terrain` and takes its seed and size
+    pub fn terrain(mut self, terrain: Terrain) -> SimulationBuilder {
+        self.seed = terrain.seed();
+        self.area_size = terrain.area_size();
+        self.terrain = Some(terrain);
+        self
+    }
+
+    pub fn npc(mut self, npc: NpcBuilder) -> SimulationBuilder {
+        self.npcs.push(npc);
+        self
+    }
+
+    pub fn player(mut self, player: PlayerBuilder) -> SimulationBuilder {
+        self.player = player;
+        self
+    }
+
+    //what build would generate the terrain with, None when it was given one
+    pub fn terrain_generator(&self) -> Option<TerrainGenerator> {
+        match self.terrain {
+            Some(_) => None,
+            None => Some(TerrainGenerator::new(self.seed, self.area_size)),
+        }
+    }
+
+    pub fn build(self) -> Simulation {
+        let mut world = World::new();
+        for npc in self.npcs {
+            npc.spawn(&mut world);
+        }
+        //spawned last so it is drawn on top
+        let player = self.player.spawn(&mut world);
+        spatial_system(&mut world);
+        Simulation {
+            world,
+            terrain: self.terrain.unwrap_or_else(|| Terrain::generate_sized(self.seed, self.area_size)),
+            player,
+            timestep: FixedTimestep::new(self.tick_rate),
+            rng: Random::new(self.seed),
+        }
+    }
+}
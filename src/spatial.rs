@@ -0,0 +1,81 @@
+use crate::*;
+use std::collections::HashMap;
+
+//spatial hash with one cell per chunk, tracks where every entity is
+#[derive(Default)]
+pub struct SpatialIndex {
+    cells: HashMap<(usize, usize), Vec<Entity>>,
+    positions: Storage<(Vec2, (usize, usize))>,
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        SpatialIndex::default()
+    }
+
+    //inserts the entity or moves it, only touches the cells when it changed chunk
+    pub fn update(&mut self, entity: Entity, pos: Vec2) {
+        let coords = Chunk::pos_to_coords(pos);
+        if let Some((last_pos, last_coords)) = self.positions.get_mut(entity) {
+            *last_pos = pos;
+            if *last_coords == coords {
+                return;
+            }
+            let last_coords = std::mem::replace(last_coords, coords);
+            self.remove_from_cell(entity, last_coords);
+        } else {
+            self.positions.insert(entity, (pos, coords));
+        }
+        self.cells.entry(coords).or_default().push(entity);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        if let Some((_, coords)) = self.positions.remove(entity) {
+            self.remove_from_cell(entity, coords);
+        }
+    }
+
+    fn remove_from_cell(&mut self, entity: Entity, coords: (usize, usize)) {
+        if let Some(cell) = self.cells.get_mut(&coords) {
+            cell.retain(|e| *e != entity);
+            if cell.is_empty() {
+                self.cells.remove(&coords);
+            }
+        }
+    }
+
+    pub fn position(&self, entity: Entity) -> Option<Vec2> {
+        self.positions.get(entity).map(|(pos, _)| *pos)
+    }
+
+    pub fn cell(&self, coords: (usize, usize)) -> &[Entity] {
+        self.cells.get(&coords).map_or(&[], |cell| cell.as_slice())
+    }
+
+    //every entity in the chunks from (x1, y1) to (x2, y2) inclusive
+    pub fn query_coords(
+        &self,
+        (x1, y1): (usize, usize),
+        (x2, y2): (usize, usize)
+    ) -> impl Iterator<Item = Entity> + '_ {
+        (y1..=y2).flat_map(move |y| (x1..=x2).flat_map(move |x| self.cell((x, y)).iter().copied()))
+    }
+
+    pub fn query_rect(&self, min: Vec2, max: Vec2) -> Vec<Entity> {
+        self.query_coords(Chunk::pos_to_coords(min), Chunk::pos_to_coords(max))
+            .filter(|entity| {
+                let pos = self.positions.get(*entity).unwrap().0;
+                pos.x >= min.x && pos.y >= min.y && pos.x <= max.x && pos.y <= max.y
+            })
+            .collect()
+    }
+
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<Entity> {
+        let offset = Vec2::new(radius, radius);
+        self.query_coords(Chunk::pos_to_coords(center - offset), Chunk::pos_to_coords(center + offset))
+            .filter(|entity| {
+                self.positions.get(*entity).unwrap().0.distance_squared(center) <= radius * radius
+            })
+            .collect()
+    }
+}
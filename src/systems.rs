@@ -0,0 +1,182 @@
+use crate::*;
+use notan::random::utils::Random;
+
+//systems are run in the order they are declared here, input_system once per frame,
+//the rest up to collision_system once per fixed tick, animation_system once per frame
+//and render_system and name_system from draw
+
+pub fn input_system(world: &mut World, input: &Input) {
+    let movement = input.movement;
+    for (entity, player_input) in world.inputs.iter_mut() {
+        let (Some(transform), Some(intent)) = (
+            world.transforms.get(entity),
+            world.intents.get_mut(entity),
+        ) else {
+            continue;
+        };
+        if let (false, Some(aim)) = (player_input.has_path(), input.aim) {
+            intent.desired_rotation = Angle::between(transform.position().vec2(), aim);
+        }
+        if movement.length() != 0.0 {
+            player_input.clear_path();
+            intent.movement = player_input.model.to_local(
+                movement,
+                intent.desired_rotation
+            );
+        } else {
+            intent.movement = player_input.follow_path(transform, &mut intent.desired_rotation);
+        }
+    }
+}
+
+//keeps the state before this tick around for render interpolation
+pub fn begin_tick_system(world: &mut World) {
+    for (_, transform) in world.transforms.iter_mut() {
+        transform.begin_tick();
+    }
+}
+
+pub fn ai_system(world: &mut World, delta: f32, rng: &mut Random) {
+    for (entity, behaviour) in world.behaviours.iter_mut() {
+        let (Some(transform), Some(intent)) = (
+            world.transforms.get(entity),
+            world.intents.get_mut(entity),
+        ) else {
+            continue;
+        };
+        let pos = transform.position().vec();
+        let target = world.spatial
+            .query_radius(pos, SENSE_RADIUS)
+            .into_iter()
+            .filter(|other| world.players.get(*other).is_some())
+            .filter_map(|other| world.spatial.position(other))
+            .min_by(|a, b| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)));
+        intent.movement = behaviour.think(
+            transform,
+            &mut intent.desired_rotation,
+            delta,
+            target,
+            rng
+        );
+    }
+}
+
+//entities driven by network snapshots are skipped, the server moves them
+pub fn movement_system(world: &mut World, terrain: &Terrain, delta: f32) {
+    for (entity, transform) in world.transforms.iter_mut() {
+        if world.networked.get(entity).is_some() {
+            continue;
+        }
+        if let Some(intent) = world.intents.get(entity) {
+            let surface = terrain.surface_at(transform.position().vec());
+            transform.update(delta, intent.movement, intent.desired_rotation, surface);
+        }
+    }
+}
+
+pub fn spatial_system(world: &mut World) {
+    for (entity, transform) in world.transforms.iter() {
+        world.spatial.update(entity, transform.position().vec());
+    }
+}
+
+//pushes overlapping colliders apart, each takes half of the overlap
+pub fn collision_system(world: &mut World) {
+    let max_radius = world.colliders
+        .iter()
+        .map(|(_, collider)| collider.radius)
+        .fold(0.0, f32::max);
+    let mut pushes = Vec::new();
+
+    for (a, collider) in world.colliders.iter() {
+        let Some(a_pos) = world.spatial.position(a) else {
+            continue;
+        };
+        for b in world.spatial.query_radius(a_pos, collider.radius + max_radius) {
+            //every pair is handled once, from the entity with the lower id
+            if b <= a {
+                continue;
+            }
+            let (Some(other), Some(b_pos)) = (world.colliders.get(b), world.spatial.position(b)) else {
+                continue;
+            };
+            let delta = b_pos - a_pos;
+            let overlap = collider.radius + other.radius - delta.length();
+            if overlap <= 0.0 {
+                continue;
+            }
+            let dir = match delta.try_normalize() {
+                Some(dir) => dir,
+                None => Vec2::new(1.0, 0.0),
+            };
+            let push = dir * (overlap / 2.0);
+            pushes.push((a, -push));
+            pushes.push((b, push));
+        }
+    }
+
+    for (entity, push) in pushes {
+        if let Some(transform) = world.transforms.get_mut(entity) {
+            transform.mut_position().offset(push);
+            world.spatial.update(entity, transform.position().vec());
+        }
+    }
+}
+
+//steps animations by the frame delta, runs from update once per frame
+pub fn animation_system(world: &mut World, delta: f32) {
+    for (entity, renderable) in world.renderables.iter_mut() {
+        let (Renderable::Animated { animation, .. }, Some(transform)) = (
+            renderable,
+            world.transforms.get(entity),
+        ) else {
+            continue;
+        };
+        animation.advance(delta, transform.speed(), transform.movement_speed());
+    }
+}
+
+//only renders entities inside the visible chunk range, clamped to the world like the chunks,
+//`alpha` blends between the last two ticks
+pub fn render_system(
+    world: &World,
+    draw: &mut Draw,
+    (x1, y1): (usize, usize),
+    (x2, y2): (usize, usize),
+    alpha: f32
+) {
+    let mut visible: Vec<Entity> = world.spatial.query_coords((x1, y1), (x2, y2)).collect();
+    //keeps spawn order so the player stays on top
+    visible.sort_unstable();
+    for entity in visible {
+        if let (Some(renderable), Some(transform)) = (
+            world.renderables.get(entity),
+            world.transforms.get(entity),
+        ) {
+            let (pos, degrees) = transform.interpolated(alpha);
+            renderable.render(draw, pos, degrees);
+        }
+    }
+}
+
+//display names in world space just above the players, after render_system so they stay on top
+pub fn name_system(
+    world: &World,
+    draw: &mut Draw,
+    font: &Font,
+    (x1, y1): (usize, usize),
+    (x2, y2): (usize, usize),
+    alpha: f32
+) {
+    for entity in world.spatial.query_coords((x1, y1), (x2, y2)) {
+        if let (Some(player), Some(transform)) = (world.players.get(entity), world.transforms.get(entity)) {
+            let (pos, _) = transform.interpolated(alpha);
+            draw.text(font, player.display_name())
+                .position(pos.x, pos.y - 9.0)
+                .size(6.0)
+                .h_align_center()
+                .v_align_bottom()
+                .color(Color::WHITE);
+        }
+    }
+}
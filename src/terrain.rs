@@ -0,0 +1,300 @@
+use crate::*;
+use notan::random::rand::Rng;
+use notan::random::utils::Random;
+use std::io;
+use std::path::Path;
+
+pub const CHUNK_TILES: usize = 16;
+//floor tiles with their own surface, the teal one is ice and the brown one mud
+const ICE_TILE: u8 = 2;
+const MUD_TILE: u8 = 5;
+//the wall the editor builds, generated walls use any tile above 16
+const BUILT_WALL_TILE: u8 = 17;
+const MAGIC: &[u8; 4] = b"IWLD";
+const VERSION: u8 = 1;
+
+//tile data of one chunk, everything needed to simulate it without a window
+#[derive(Clone, PartialEq)]
+pub struct ChunkTiles {
+    floor_tiles: Vec<u8>,
+    wall_tiles: Vec<u8>,
+}
+
+impl ChunkTiles {
+    pub fn generate(rng: &mut Random) -> Self {
+        let mut floor_tiles = Vec::with_capacity(CHUNK_TILES * CHUNK_TILES);
+        let mut wall_tiles = Vec::with_capacity(CHUNK_TILES * CHUNK_TILES);
+        for _ in 0..CHUNK_TILES * CHUNK_TILES {
+            floor_tiles.push((rng.gen::<f32>() * 6.0) as u8);
+            wall_tiles.push((rng.gen::<f32>() * 22.0) as u8);
+        }
+        ChunkTiles { floor_tiles, wall_tiles }
+    }
+
+    //the atlas index that is drawn for tile i, walls cover the floor
+    pub fn visible_tile(&self, i: usize) -> u8 {
+        match self.wall_tiles[i] {
+            x if x > 16 => x,
+            _ => self.floor_tiles[i],
+        }
+    }
+
+    pub fn surface(&self, x: usize, y: usize) -> Surface {
+        match self.floor_tiles[x + y * CHUNK_TILES] {
+            ICE_TILE => Surface { grip: 0.15, speed: 1.0 },
+            MUD_TILE => Surface { grip: 2.0, speed: 0.5 },
+            _ => Surface::default(),
+        }
+    }
+
+    //x and y are tile coordinates inside this chunk
+    pub fn is_wall(&self, x: usize, y: usize) -> bool {
+        self.wall_tiles[x + y * CHUNK_TILES] > 16
+    }
+
+    //puts a wall on the floor or takes it away
+    pub fn set_wall(&mut self, x: usize, y: usize, wall: bool) {
+        self.wall_tiles[x + y * CHUNK_TILES] = if wall { BUILT_WALL_TILE } else { 0 };
+    }
+
+    //atlas indices above 16 are walls on the floor that is there, the others replace the floor
+    //and take away the wall
+    pub fn set_tile(&mut self, x: usize, y: usize, tile: u8) {
+        let i = x + y * CHUNK_TILES;
+        if tile > 16 {
+            self.wall_tiles[i] = tile;
+        } else {
+            self.floor_tiles[i] = tile;
+            self.wall_tiles[i] = 0;
+        }
+    }
+
+    fn floor_tile(&self, x: usize, y: usize) -> u8 {
+        self.floor_tiles[x + y * CHUNK_TILES]
+    }
+}
+
+//tile counts of a whole world
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct TerrainStats {
+    pub tiles: usize,
+    pub walls: usize,
+    //floor tiles by surface, walls not included
+    pub ice: usize,
+    pub mud: usize,
+    pub floor: usize,
+}
+
+//makes a terrain a few chunks at a time so a loading screen can show how far it got
+pub struct TerrainGenerator {
+    seed: u64,
+    area_size: usize,
+    chunks: Vec<ChunkTiles>,
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: u64, area_size: usize) -> Self {
+        TerrainGenerator { seed, area_size, chunks: Vec::with_capacity(area_size * area_size) }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.chunks.len() == self.area_size * self.area_size
+    }
+
+    //0 before the first chunk, 1 when every chunk is there
+    pub fn progress(&self) -> f32 {
+        (self.chunks.len() as f32) / ((self.area_size * self.area_size).max(1) as f32)
+    }
+
+    //generates the next chunk in row order, false once there is none left
+    pub fn step(&mut self) -> bool {
+        if self.is_done() {
+            return false;
+        }
+        let i = self.chunks.len();
+        let coords = (i % self.area_size, i / self.area_size);
+        self.chunks.push(ChunkTiles::generate(&mut Terrain::chunk_rng(self.seed, self.area_size, coords)));
+        true
+    }
+
+    //generates whatever is left
+    pub fn finish(mut self) -> Terrain {
+        while self.step() {}
+        Terrain { seed: self.seed, area_size: self.area_size, chunks: self.chunks }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Terrain {
+    seed: u64,
+    //width and height in chunks
+    area_size: usize,
+    chunks: Vec<ChunkTiles>,
+}
+
+impl Terrain {
+    //a world of the default AREA_SIZE
+    pub fn generate(seed: u64) -> Self {
+        Terrain::generate_sized(seed, AREA_SIZE)
+    }
+
+    pub fn generate_sized(seed: u64, area_size: usize) -> Self {
+        TerrainGenerator::new(seed, area_size).finish()
+    }
+
+    //every chunk has its own generator so one can be regenerated without touching the rest
+    fn chunk_rng(seed: u64, area_size: usize, (x, y): (usize, usize)) -> Random {
+        Random::new(seed ^ (((x + y * area_size) as u64).wrapping_mul(0x9e3779b97f4a7c15)))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn area_size(&self) -> usize {
+        self.area_size
+    }
+
+    pub fn chunk(&self, (x, y): (usize, usize)) -> Option<&ChunkTiles> {
+        if x >= self.area_size {
+            return None;
+        }
+        self.chunks.get(x + y * self.area_size)
+    }
+
+    //width and height of the whole world in tiles
+    pub fn size_in_tiles(&self) -> usize {
+        self.area_size * CHUNK_TILES
+    }
+
+    //x and y are tile coordinates in the whole world, anything outside of it counts as a wall
+    pub fn is_wall(&self, (x, y): (usize, usize)) -> bool {
+        match self.chunk((x / CHUNK_TILES, y / CHUNK_TILES)) {
+            Some(chunk) => chunk.is_wall(x % CHUNK_TILES, y % CHUNK_TILES),
+            None => true,
+        }
+    }
+
+    //the atlas index drawn at a tile of the whole world, None outside of it
+    pub fn visible_tile(&self, (x, y): (usize, usize)) -> Option<u8> {
+        if x >= self.size_in_tiles() {
+            return None;
+        }
+        let chunk = self.chunk((x / CHUNK_TILES, y / CHUNK_TILES))?;
+        Some(chunk.visible_tile(x % CHUNK_TILES + (y % CHUNK_TILES) * CHUNK_TILES))
+    }
+
+    //the coordinates of the chunk that changed, None when the tile is outside the world or
+    //already was that
+    pub fn set_wall(&mut self, (x, y): (usize, usize), wall: bool) -> Option<(usize, usize)> {
+        let coords = (x / CHUNK_TILES, y / CHUNK_TILES);
+        if coords.0 >= self.area_size || coords.1 >= self.area_size || self.is_wall((x, y)) == wall {
+            return None;
+        }
+        self.chunks[coords.0 + coords.1 * self.area_size].set_wall(x % CHUNK_TILES, y % CHUNK_TILES, wall);
+        Some(coords)
+    }
+
+    //like set_wall with any atlas index, see ChunkTiles::set_tile
+    pub fn set_tile(&mut self, (x, y): (usize, usize), tile: u8) -> Option<(usize, usize)> {
+        let coords = (x / CHUNK_TILES, y / CHUNK_TILES);
+        if coords.0 >= self.area_size || coords.1 >= self.area_size {
+            return None;
+        }
+        self.chunks[coords.0 + coords.1 * self.area_size].set_tile(x % CHUNK_TILES, y % CHUNK_TILES, tile);
+        Some(coords)
+    }
+
+    //puts back the generated tiles of one chunk, false when it is outside the world
+    pub fn regenerate_chunk(&mut self, coords: (usize, usize)) -> bool {
+        if coords.0 >= self.area_size || coords.1 >= self.area_size {
+            return false;
+        }
+        let mut rng = Terrain::chunk_rng(self.seed, self.area_size, coords);
+        self.chunks[coords.0 + coords.1 * self.area_size] = ChunkTiles::generate(&mut rng);
+        true
+    }
+
+    //the ground at a world position, outside of the world it is plain floor
+    pub fn surface_at(&self, pos: Vec2) -> Surface {
+        let (x, y) = Chunk::pos_to_tile(pos);
+        match self.chunk((x / CHUNK_TILES, y / CHUNK_TILES)) {
+            Some(chunk) => chunk.surface(x % CHUNK_TILES, y % CHUNK_TILES),
+            None => Surface::default(),
+        }
+    }
+
+    pub fn stats(&self) -> TerrainStats {
+        let mut stats = TerrainStats::default();
+        for chunk in &self.chunks {
+            for y in 0..CHUNK_TILES {
+                for x in 0..CHUNK_TILES {
+                    stats.tiles += 1;
+                    let count = match chunk.floor_tile(x, y) {
+                        _ if chunk.is_wall(x, y) => &mut stats.walls,
+                        ICE_TILE => &mut stats.ice,
+                        MUD_TILE => &mut stats.mud,
+                        _ => &mut stats.floor,
+                    };
+                    *count += 1;
+                }
+            }
+        }
+        stats
+    }
+
+    //the header holds the seed and the size, then every chunk's floor and wall tiles row by row
+    pub fn encode(&self) -> Vec<u8> {
+        let tiles = CHUNK_TILES * CHUNK_TILES;
+        let mut bytes = Vec::with_capacity(15 + self.chunks.len() * tiles * 2);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(self.area_size as u16).to_le_bytes());
+        for chunk in &self.chunks {
+            bytes.extend_from_slice(&chunk.floor_tiles);
+            bytes.extend_from_slice(&chunk.wall_tiles);
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(0..4)? != MAGIC || *bytes.get(4)? != VERSION {
+            return None;
+        }
+        let seed = u64::from_le_bytes(bytes.get(5..13)?.try_into().ok()?);
+        let area_size = u16::from_le_bytes(bytes.get(13..15)?.try_into().ok()?) as usize;
+        let tiles = CHUNK_TILES * CHUNK_TILES;
+        if area_size == 0 || bytes.len() != 15 + area_size * area_size * tiles * 2 {
+            return None;
+        }
+        let chunks = bytes[15..]
+            .chunks(tiles * 2)
+            .map(|chunk| ChunkTiles {
+                floor_tiles: chunk[..tiles].to_vec(),
+                wall_tiles: chunk[tiles..].to_vec(),
+            })
+            .collect();
+        Some(Terrain { seed, area_size, chunks })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.encode())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Terrain::decode(&std::fs::read(path)?).ok_or_else(||
+            io::Error::new(io::ErrorKind::InvalidData, "not a world file")
+        )
+    }
+
+    //waypoints at tile centers from `from` to `to` around walls
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        let size = self.size_in_tiles();
+        pathfinding
+            ::find_path(Chunk::pos_to_tile(from), Chunk::pos_to_tile(to), (size, size), |tile|
+                self.is_wall(tile)
+            )
+            .map(|path| path.into_iter().map(Chunk::tile_center).collect())
+    }
+}
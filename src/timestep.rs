@@ -0,0 +1,59 @@
+pub const DEFAULT_TICK_RATE: f32 = 60.0;
+//after a long hitch the simulation skips ahead instead of trying to catch up forever
+const MAX_TICKS_PER_FRAME: u32 = 8;
+//tick rates are kept between these, nan and infinity become the default
+pub const MIN_TICK_RATE: f32 = 1.0;
+pub const MAX_TICK_RATE: f32 = 1000.0;
+
+//accumulates frame time and hands it out as whole simulation ticks
+pub struct FixedTimestep {
+    delta: f32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(tick_rate: f32) -> Self {
+        FixedTimestep {
+            delta: tick_delta(tick_rate),
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn tick_rate(&self) -> f32 {
+        1.0 / self.delta
+    }
+
+    pub fn set_tick_rate(&mut self, tick_rate: f32) {
+        self.delta = tick_delta(tick_rate);
+    }
+
+    //length of one tick in seconds
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    //adds the frame time and returns how many ticks to run now
+    //a frame time that is negative or not a number adds nothing
+    pub fn advance(&mut self, frame_delta: f32) -> u32 {
+        if frame_delta.is_finite() && frame_delta > 0.0 {
+            self.accumulator += frame_delta;
+        }
+        let ticks = (self.accumulator / self.delta) as u32;
+        self.accumulator -= (ticks as f32) * self.delta;
+        if ticks > MAX_TICKS_PER_FRAME {
+            self.accumulator = 0.0;
+            return MAX_TICKS_PER_FRAME;
+        }
+        ticks
+    }
+
+    //how far between the last two ticks the current frame is, for render interpolation
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.delta).clamp(0.0, 1.0)
+    }
+}
+
+fn tick_delta(tick_rate: f32) -> f32 {
+    let tick_rate = if tick_rate.is_finite() { tick_rate } else { DEFAULT_TICK_RATE };
+    1.0 / tick_rate.clamp(MIN_TICK_RATE, MAX_TICK_RATE)
+}
@@ -1,10 +1,70 @@
 use crate::*;
 
+const WAYPOINT_RADIUS: f32 = 2.0;
+const WALK_MAX_ANGLE: f32 = 30.0;
+
+//how fast an entity walks in each direction relative to where it faces, as parts of movement_speed
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DirectionSpeeds {
+    pub forward: f32,
+    pub back: f32,
+    pub strafe: f32,
+}
+
+impl Default for DirectionSpeeds {
+    fn default() -> Self {
+        DirectionSpeeds {
+            forward: 1.0,
+            back: 0.5,
+            strafe: 0.75,
+        }
+    }
+}
+
+impl DirectionSpeeds {
+    //`local` is a movement vector where -y is forward
+    pub fn scale(&self, local: Vec2) -> Vec2 {
+        Vec2::new(
+            local.x * self.strafe,
+            local.y * (if local.y < 0.0 { self.forward } else { self.back })
+        )
+    }
+}
+
+//what the ground does to an entity walking on it
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Surface {
+    //scales acceleration and braking, low on ice
+    pub grip: f32,
+    //scales the top speed, low in mud
+    pub speed: f32,
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Surface { grip: 1.0, speed: 1.0 }
+    }
+}
+
 pub struct TransformPR {
     rotation: Rotation,
     position: Position,
+    velocity: Vec2,
+    //degrees per second
     rotation_speed: f32,
+    turn_easing: TurnEasing,
     movement_speed: f32,
+    //units per second squared, towards the wanted velocity while there is input
+    acceleration: f32,
+    //units per second squared, towards standing still while there is none
+    friction: f32,
+    direction_speeds: DirectionSpeeds,
+}
+
+impl Default for TransformPR {
+    fn default() -> Self {
+        TransformPR::new()
+    }
 }
 
 impl TransformPR {
@@ -12,11 +72,43 @@ impl TransformPR {
         TransformPR {
             rotation: Rotation::zero(),
             position: Position::new(2048.0, 2048.0),
+            velocity: Vec2::new(0.0, 0.0),
             rotation_speed: 150.0,
+            turn_easing: TurnEasing::Linear,
             movement_speed: 50.0,
+            acceleration: 600.0,
+            friction: 600.0,
+            direction_speeds: DirectionSpeeds::default(),
         }
     }
 
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.position = Position::new(x, y);
+        self
+    }
+
+    pub fn with_movement_speed(mut self, movement_speed: f32) -> Self {
+        self.movement_speed = movement_speed;
+        self
+    }
+
+    pub fn with_turning(mut self, rotation_speed: f32, turn_easing: TurnEasing) -> Self {
+        self.rotation_speed = rotation_speed;
+        self.turn_easing = turn_easing;
+        self
+    }
+
+    pub fn with_acceleration(mut self, acceleration: f32, friction: f32) -> Self {
+        self.acceleration = acceleration;
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_direction_speeds(mut self, direction_speeds: DirectionSpeeds) -> Self {
+        self.direction_speeds = direction_speeds;
+        self
+    }
+
     pub fn pos_touple(&self) -> (f32, f32) {
         self.position.touple()
     }
@@ -28,46 +120,121 @@ impl TransformPR {
         &mut self.position
     }
 
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    pub fn movement_speed(&self) -> f32 {
+        self.movement_speed
+    }
+
+    //units per second, from the velocity and not from how far the last tick went
+    pub fn speed(&self) -> f32 {
+        self.velocity.length()
+    }
+
+    //sets the velocity without going through acceleration, for snapshots and replays
+    pub fn set_velocity(&mut self, velocity: Vec2) {
+        self.velocity = velocity;
+    }
+
+    //pushes the entity, the push fades through friction and can exceed the top speed
+    pub fn knockback(&mut self, impulse: Vec2) {
+        self.velocity += impulse;
+    }
+
     pub fn rotation(&self) -> &Rotation {
         &self.rotation
     }
+    pub fn mut_rotation(&mut self) -> &mut Rotation {
+        &mut self.rotation
+    }
+
+    //remembers the current state as the previous one, called at the start of every tick
+    pub fn begin_tick(&mut self) {
+        self.position.begin_tick();
+        self.rotation.begin_tick();
+    }
+
+    //position and degrees blended between the previous and the current tick
+    pub fn interpolated(&self, alpha: f32) -> (Vec2, f32) {
+        (self.position.interpolated(alpha), self.rotation.interpolated(alpha))
+    }
+
+    pub fn reached(&self, target: Vec2) -> bool {
+        target.distance(self.position.vec()) <= WAYPOINT_RADIUS
+    }
+
+    //points `desired_rotation` at the target and returns the input vector to walk there,
+    //which stays zero until roughly facing it so turning still respects rotation_speed
+    pub fn seek(&self, desired_rotation: &mut Angle, target: Vec2) -> Vec2 {
+        *desired_rotation = Angle::between(self.position.vec2(), target);
+        if self.rotation.difference(*desired_rotation).abs() > WALK_MAX_ANGLE {
+            return Vec2::new(0.0, 0.0);
+        }
+        Vec2::new(0.0, -1.0)
+    }
 
     fn angle_to_speed(&self, angle: f32) -> f32 {
         (360.0 - angle.abs()) / 360.0
     }
 
-    pub fn update(&mut self, app: &mut App, vel_vec: Vec2, desired_rotation: f32) {
-        let dif = self.update_rotation(app, desired_rotation);
+    //`vel_vec` is relative to `desired_rotation`, -y walks towards it, `surface` is the ground
+    //under the entity at the start of the tick
+    pub fn update(&mut self, delta: f32, vel_vec: Vec2, desired_rotation: Angle, surface: Surface) {
+        let dif = self.update_rotation(delta, desired_rotation);
         if vel_vec.length() == 0.0 {
+            let braking = self.friction * surface.grip * delta;
+            self.velocity = self.velocity.clamp_length_max((self.speed() - braking).max(0.0));
+        } else {
+            let wanted =
+                desired_rotation
+                    .vec()
+                    .rotate(self.direction_speeds.scale(vel_vec))
+                    .perp() *
+                self.angle_to_speed(dif) *
+                self.movement_speed *
+                surface.speed;
+            let step = (wanted - self.velocity).clamp_length_max(
+                self.acceleration * surface.grip * delta
+            );
+            self.velocity += step;
+        }
+        if self.velocity.length() == 0.0 {
             return;
         }
-        let dir =
-            Vec2::from_angle(desired_rotation.to_radians()).rotate(vel_vec).perp() *
-            self.angle_to_speed(dif) *
-            self.movement_speed *
-            app.timer.delta_f32();
-        self.position.add_vec(&dir);
-    }
-
-    fn update_rotation(&mut self, app: &mut App, desired_rotation: f32) -> f32 {
-        if
-            self.rotation.smooth_degrees() > desired_rotation &&
-            self.rotation().last_degrees() < desired_rotation
-        {
-            return 0.0;
+        self.position.add_vec(&(self.velocity * delta));
+    }
+
+    //turns towards the desired rotation and returns how far off it was before turning
+    fn update_rotation(&mut self, delta: f32, desired_rotation: Angle) -> f32 {
+        let difference = self.rotation.difference(desired_rotation);
+        let step = self.rotation_speed * self.turn_easing.factor(difference) * delta;
+        self.rotation.set(self.rotation.angle().rotate_towards(desired_rotation, step));
+        difference
+    }
+}
+
+//how turning slows down close to the desired rotation
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub enum TurnEasing {
+    //full rotation_speed until facing the target
+    #[default]
+    Linear,
+    //slows down over the last `degrees`, never below a tenth of rotation_speed
+    EaseOut {
+        degrees: f32,
+    },
+}
+
+impl TurnEasing {
+    //part of rotation_speed to turn with while `difference` degrees away from the target
+    pub fn factor(&self, difference: f32) -> f32 {
+        match self {
+            TurnEasing::Linear => 1.0,
+            TurnEasing::EaseOut { degrees } if *degrees > 0.0 => (difference.abs() / degrees).clamp(0.1, 1.0),
+            TurnEasing::EaseOut { .. } => 1.0,
         }
-        let angle = match desired_rotation - (self.rotation.smooth_degrees() % 360.0) {
-            x if x.abs() < 1.0 => {
-                self.rotation.set_smooth(desired_rotation);
-                return 0.0;
-            }
-            x => x,
-        };
-        let dir = ((angle + 180.0) % 360.0) - 180.0;
-        self.rotation.add_smooth(
-            (dir.clamp(-1.0, 1.0) as f32) * self.rotation_speed * app.timer.delta_f32()
-        );
-        dir
     }
 }
 
@@ -79,9 +246,6 @@ pub struct Position {
 }
 
 impl Position {
-    pub fn speed_max_xy(&self) -> f32 {
-        f32::max((self.last_x - self.x).abs(), self.last_y - self.y)
-    }
     pub fn new(x: f32, y: f32) -> Self {
         Position {
             x,
@@ -97,6 +261,20 @@ impl Position {
         self.y = new_y;
     }
 
+    //sets the position without it counting as movement
+    pub fn reset(&mut self, x: f32, y: f32) {
+        *self = Position::new(x, y);
+    }
+
+    pub fn begin_tick(&mut self) {
+        self.last_x = self.x;
+        self.last_y = self.y;
+    }
+
+    pub fn interpolated(&self, alpha: f32) -> Vec2 {
+        Vec2::new(self.last_x, self.last_y).lerp(self.vec(), alpha)
+    }
+
     fn touple(&self) -> (f32, f32) {
         (self.x, self.y)
     }
@@ -129,6 +307,12 @@ impl Position {
         self.set(self.x + x, self.y + y);
     }
 
+    //moves without touching last_x/last_y, for corrections that are not movement
+    pub fn offset(&mut self, vec: Vec2) {
+        self.x += vec.x;
+        self.y += vec.y;
+    }
+
     pub fn add_vec(&mut self, vec: &Vec2) {
         self.set(self.x + vec.x, self.y + vec.y);
     }
@@ -138,43 +322,58 @@ impl Position {
     }
 }
 
+//the facing of an entity, with the facing of the previous tick for render interpolation
 pub struct Rotation {
-    degrees: f32,
-    last_degrees: f32,
+    angle: Angle,
+    last: Angle,
 }
 
 impl Rotation {
     pub fn zero() -> Self {
-        Rotation {
-            degrees: 0.0,
-            last_degrees: 0.0,
-        }
+        Rotation::from_angle(Angle::ZERO)
     }
-    pub fn smooth_degrees(&self) -> f32 {
-        self.degrees
+
+    pub fn from_degrees(degrees: f32) -> Self {
+        Rotation::from_angle(Angle::from_degrees(degrees))
     }
-    pub fn degrees_normalized(&self) -> f32 {
-        self.degrees % 360.0
+
+    pub fn from_angle(angle: Angle) -> Self {
+        Rotation { angle, last: angle }
     }
 
-    pub fn set_smooth(&mut self, degrees: f32) {
-        self.last_degrees = self.degrees;
-        self.degrees = (degrees % 360.0) + 360.0;
+    pub fn angle(&self) -> Angle {
+        self.angle
     }
-    pub fn set(&mut self, degrees: f32) {
-        self.last_degrees = self.degrees;
-        self.degrees = degrees % 360.0;
+
+    //0..360
+    pub fn degrees(&self) -> f32 {
+        self.angle.degrees()
+    }
+
+    pub fn set(&mut self, angle: Angle) {
+        self.angle = angle;
+    }
+
+    //sets the facing without it counting as turning
+    pub fn reset(&mut self, angle: Angle) {
+        *self = Rotation::from_angle(angle);
+    }
+
+    pub fn begin_tick(&mut self) {
+        self.last = self.angle;
     }
 
-    pub fn add_smooth(&mut self, degrees: f32) {
-        self.set_smooth(self.degrees + degrees);
+    //degrees blended along the shorter way between the previous and the current facing
+    pub fn interpolated(&self, alpha: f32) -> f32 {
+        self.last.lerp(self.angle, alpha).degrees()
     }
 
     pub fn changed(&self) -> bool {
-        self.last_degrees != self.degrees
+        self.last != self.angle
     }
 
-    fn last_degrees(&self) -> f32 {
-        self.last_degrees
+    //shortest signed turn from this rotation to `angle`, in -180..180
+    pub fn difference(&self, angle: Angle) -> f32 {
+        self.angle.difference(angle)
     }
 }
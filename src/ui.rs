@@ -0,0 +1,419 @@
+use crate::*;
+
+const ROW_HEIGHT: f32 = 28.0;
+const GRAPH_HEIGHT: f32 = 48.0;
+const SPACING: f32 = 6.0;
+const PADDING: f32 = 12.0;
+const TEXT_SIZE: f32 = 16.0;
+const TITLE_SIZE: f32 = 22.0;
+
+const PANEL_COLOR: Color = Color::new(0.05, 0.05, 0.08, 0.85);
+const WIDGET_COLOR: Color = Color::new(0.2, 0.2, 0.25, 1.0);
+const HOT_COLOR: Color = Color::new(0.35, 0.35, 0.45, 1.0);
+const FILL_COLOR: Color = Color::new(0.45, 0.6, 0.9, 1.0);
+
+fn contains(rect: &Rect, point: Vec2) -> bool {
+    point.x >= rect.x && point.x < rect.max_x() && point.y >= rect.y && point.y < rect.max_y()
+}
+
+//what the widgets react to, gathered once a frame
+#[derive(Clone, PartialEq, Debug)]
+pub struct UiInput {
+    pub mouse: Vec2,
+    //the left button went down this frame and is down
+    pub pressed: bool,
+    pub down: bool,
+    //characters typed this frame
+    pub text: String,
+    pub keys: Vec<KeyCode>,
+}
+
+impl UiInput {
+    //for screens that are covered, nothing is hovered or pressed
+    pub fn none() -> Self {
+        UiInput {
+            mouse: Vec2::new(f32::NAN, f32::NAN),
+            pressed: false,
+            down: false,
+            text: String::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    //`text` is what Event::ReceivedCharacter brought since the last frame
+    pub fn from_app(app: &App, text: &str) -> Self {
+        let keys = [
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Tab,
+            KeyCode::Return,
+            KeyCode::Escape,
+            KeyCode::Back,
+        ];
+        UiInput {
+            mouse: app.mouse.position().into(),
+            pressed: app.mouse.left_was_pressed(),
+            down: app.mouse.left_is_down(),
+            text: text.to_string(),
+            keys: keys.into_iter().filter(|key| app.keyboard.was_pressed(*key)).collect(),
+        }
+    }
+
+    fn key(&self, key: KeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+}
+
+//how a widget is drawn this frame
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct WidgetLook {
+    pub hovered: bool,
+    pub focused: bool,
+    pub active: bool,
+}
+
+impl WidgetLook {
+    fn color(&self) -> Color {
+        if self.hovered || self.focused || self.active { HOT_COLOR } else { WIDGET_COLOR }
+    }
+}
+
+//what the ui remembers between frames, widgets are told apart by the order they are made in,
+//so a screen has to make the same widgets in the same order every frame
+#[derive(Default)]
+pub struct UiState {
+    //the arrow keys and tab move between widgets and enter uses them
+    keyboard: bool,
+    //made this frame so far
+    next_widget: usize,
+    //made last frame, the keyboard focus wraps around at it
+    widgets: usize,
+    //the slider the mouse holds on to until it is let go
+    active: Option<usize>,
+    focus: Option<usize>,
+    //the text field being typed into
+    editing: Option<usize>,
+    //panel heights of last frame, the background is drawn before the contents are known
+    panel_heights: Vec<f32>,
+    next_panel: usize,
+    //everything drawn last frame and this one, to know whether the mouse is over the ui
+    areas: Vec<Rect>,
+    next_areas: Vec<Rect>,
+}
+
+impl UiState {
+    //menus take the keyboard, panels over the game only the mouse
+    pub fn with_keyboard(mut self) -> Self {
+        self.keyboard = true;
+        self.focus = Some(0);
+        self
+    }
+
+    //true when a click there belongs to the ui and not to whatever is below it
+    pub fn wants_mouse(&self, mouse: Vec2) -> bool {
+        self.active.is_some() || self.areas.iter().any(|area| contains(area, mouse))
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    pub fn begin(&mut self, input: &UiInput) {
+        self.next_widget = 0;
+        self.next_panel = 0;
+        self.areas = std::mem::take(&mut self.next_areas);
+        if !self.keyboard || self.editing.is_some() || self.widgets == 0 {
+            return;
+        }
+        let focus = self.focus.unwrap_or(self.widgets - 1);
+        if input.key(KeyCode::Down) || input.key(KeyCode::Tab) {
+            self.focus = Some((focus + 1) % self.widgets);
+        } else if input.key(KeyCode::Up) {
+            self.focus = Some((focus + self.widgets - 1) % self.widgets);
+        }
+    }
+
+    pub fn end(&mut self, input: &UiInput) {
+        self.widgets = self.next_widget;
+        if !input.down {
+            self.active = None;
+        }
+    }
+
+    //marks a part of the screen as covered by the ui
+    pub fn cover(&mut self, rect: Rect) {
+        self.next_areas.push(rect);
+    }
+
+    fn widget(&mut self, input: &UiInput, rect: Rect) -> (usize, WidgetLook) {
+        let id = self.next_widget;
+        self.next_widget += 1;
+        self.cover(rect);
+        let hovered = contains(&rect, input.mouse) && (self.active.is_none() || self.active == Some(id));
+        if hovered && self.keyboard && input.pressed {
+            self.focus = Some(id);
+        }
+        let look = WidgetLook {
+            hovered,
+            focused: self.keyboard && self.focus == Some(id),
+            active: self.active == Some(id) || self.editing == Some(id),
+        };
+        (id, look)
+    }
+
+    fn used(&self, input: &UiInput, look: &WidgetLook) -> bool {
+        (look.hovered && input.pressed) || (look.focused && input.key(KeyCode::Return))
+    }
+
+    //true when clicked or entered this frame
+    pub fn button(&mut self, input: &UiInput, rect: Rect) -> (bool, WidgetLook) {
+        let (_, look) = self.widget(input, rect);
+        (self.used(input, &look), look)
+    }
+
+    //true when `value` was flipped
+    pub fn checkbox(&mut self, input: &UiInput, rect: Rect, value: &mut bool) -> (bool, WidgetLook) {
+        let (clicked, look) = self.button(input, rect);
+        if clicked {
+            *value = !*value;
+        }
+        (clicked, look)
+    }
+
+    //dragging sets the value from where the mouse is, left and right move it a step when focused,
+    //true when `value` changed
+    pub fn slider(
+        &mut self,
+        input: &UiInput,
+        rect: Rect,
+        value: &mut f32,
+        (min, max): (f32, f32),
+        step: f32
+    ) -> (bool, WidgetLook) {
+        let (id, mut look) = self.widget(input, rect);
+        if look.hovered && input.pressed {
+            self.active = Some(id);
+            look.active = true;
+        }
+        let old = *value;
+        if self.active == Some(id) && input.down {
+            let t = ((input.mouse.x - rect.x) / rect.width).clamp(0.0, 1.0);
+            *value = min + t * (max - min);
+        } else if look.focused && input.key(KeyCode::Left) {
+            *value -= step;
+        } else if look.focused && input.key(KeyCode::Right) {
+            *value += step;
+        }
+        if step > 0.0 {
+            *value = min + ((*value - min) / step).round() * step;
+        }
+        *value = value.clamp(min, max);
+        (*value != old, look)
+    }
+
+    //a click or enter starts typing, enter, escape or a click elsewhere stops it,
+    //true when `text` changed
+    pub fn text_field(&mut self, input: &UiInput, rect: Rect, text: &mut String) -> (bool, WidgetLook) {
+        let (id, mut look) = self.widget(input, rect);
+        let editing = self.editing == Some(id);
+        if editing {
+            if input.key(KeyCode::Return) || input.key(KeyCode::Escape) || (input.pressed && !look.hovered) {
+                self.editing = None;
+                look.active = false;
+                return (false, look);
+            }
+        } else {
+            if self.used(input, &look) {
+                self.editing = Some(id);
+                look.active = true;
+            }
+            return (false, look);
+        }
+        let old = text.clone();
+        if input.key(KeyCode::Back) {
+            text.pop();
+        }
+        text.extend(input.text.chars().filter(|c| !c.is_control()));
+        (*text != old, look)
+    }
+
+    fn panel_height(&self, id: usize) -> f32 {
+        self.panel_heights.get(id).copied().unwrap_or(0.0)
+    }
+
+    fn set_panel_height(&mut self, id: usize, height: f32) {
+        if self.panel_heights.len() <= id {
+            self.panel_heights.resize(id + 1, 0.0);
+        }
+        self.panel_heights[id] = height;
+    }
+}
+
+//widgets laid out top to bottom, one Ui is made per frame for a UiState and lets go of it when
+//dropped, every widget call both handles the input and draws the widget
+pub struct Ui<'a> {
+    draw: &'a mut Draw,
+    font: &'a Font,
+    input: &'a UiInput,
+    state: &'a mut UiState,
+    x: f32,
+    y: f32,
+    width: f32,
+    //columns side by side: how many, which one is next and where the row starts
+    columns: Option<(usize, usize, f32)>,
+}
+
+impl<'a> Ui<'a> {
+    pub fn new(draw: &'a mut Draw, font: &'a Font, input: &'a UiInput, state: &'a mut UiState) -> Self {
+        state.begin(input);
+        Ui { draw, font, input, state, x: PADDING, y: PADDING, width: 300.0, columns: None }
+    }
+
+    pub fn draw(&mut self) -> &mut Draw {
+        self.draw
+    }
+
+    //where the next widget is
+    fn next_rect(&mut self, height: f32) -> Rect {
+        match &mut self.columns {
+            Some((count, next, top)) => {
+                let width = (self.width - SPACING * ((*count - 1) as f32)) / (*count as f32);
+                if *next == *count {
+                    *next = 0;
+                    *top = self.y;
+                }
+                let rect = Rect { x: self.x + (*next as f32) * (width + SPACING), y: *top, width, height };
+                *next += 1;
+                self.y = self.y.max(*top + height + SPACING);
+                rect
+            }
+            None => {
+                let rect = Rect { x: self.x, y: self.y, width: self.width, height };
+                self.y += height + SPACING;
+                rect
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str, x: f32, y: f32, size: f32, color: Color) {
+        self.draw.text(self.font, text).position(x, y).size(size).color(color).v_align_middle();
+    }
+
+    //a box of `width` at x, y holding whatever `contents` makes, with a title when it has one
+    pub fn panel<R>(&mut self, title: &str, (x, y): (f32, f32), width: f32, contents: impl FnOnce(&mut Ui) -> R) -> R {
+        let id = self.state.next_panel;
+        self.state.next_panel += 1;
+        let height = self.state.panel_height(id);
+        let background = Rect { x, y, width, height };
+        self.draw.rect((x, y), (width, height)).color(PANEL_COLOR);
+        self.state.cover(background);
+
+        let saved = (self.x, self.y, self.width, self.columns.take());
+        self.x = x + PADDING;
+        self.y = y + PADDING;
+        self.width = width - PADDING * 2.0;
+        if !title.is_empty() {
+            let rect = self.next_rect(TITLE_SIZE + 4.0);
+            self.text(title, rect.x, rect.center_y(), TITLE_SIZE, Color::WHITE);
+        }
+        let result = contents(self);
+        let height = self.y - y + PADDING - SPACING;
+        self.state.set_panel_height(id, height);
+        (self.x, self.y, self.width, self.columns) = saved;
+        result
+    }
+
+    //lays the widgets `contents` makes out in `count` columns
+    pub fn columns<R>(&mut self, count: usize, contents: impl FnOnce(&mut Ui) -> R) -> R {
+        let saved = self.columns.replace((count.max(1), 0, self.y));
+        let result = contents(self);
+        self.columns = saved;
+        result
+    }
+
+    pub fn label(&mut self, text: &str) {
+        self.colored_label(text, Color::WHITE);
+    }
+
+    pub fn colored_label(&mut self, text: &str, color: Color) {
+        let rect = self.next_rect(TEXT_SIZE + 4.0);
+        self.text(text, rect.x, rect.center_y(), TEXT_SIZE, color);
+    }
+
+    pub fn button(&mut self, text: &str) -> bool {
+        let rect = self.next_rect(ROW_HEIGHT);
+        let (clicked, look) = self.state.button(self.input, rect);
+        self.draw.rect((rect.x, rect.y), (rect.width, rect.height)).color(look.color());
+        let color = if look.focused { Color::YELLOW } else { Color::WHITE };
+        self.draw
+            .text(self.font, text)
+            .position(rect.center_x(), rect.center_y())
+            .size(TEXT_SIZE)
+            .color(color)
+            .h_align_center()
+            .v_align_middle();
+        clicked
+    }
+
+    pub fn checkbox(&mut self, text: &str, value: &mut bool) -> bool {
+        let rect = self.next_rect(ROW_HEIGHT);
+        let (changed, look) = self.state.checkbox(self.input, rect, value);
+        let size = rect.height - 8.0;
+        self.draw.rect((rect.x, rect.y + 4.0), (size, size)).color(look.color());
+        if *value {
+            self.draw.rect((rect.x + 5.0, rect.y + 9.0), (size - 10.0, size - 10.0)).color(FILL_COLOR);
+        }
+        let color = if look.focused { Color::YELLOW } else { Color::WHITE };
+        self.text(text, rect.x + size + 8.0, rect.center_y(), TEXT_SIZE, color);
+        changed
+    }
+
+    //a bar filled up to `value`, whole steps show the value without decimals
+    pub fn slider(&mut self, text: &str, value: &mut f32, range: (f32, f32), step: f32) -> bool {
+        let rect = self.next_rect(ROW_HEIGHT);
+        let (changed, look) = self.state.slider(self.input, rect, value, range, step);
+        let t = ((*value - range.0) / (range.1 - range.0)).clamp(0.0, 1.0);
+        self.draw.rect((rect.x, rect.y), (rect.width, rect.height)).color(look.color());
+        self.draw.rect((rect.x, rect.y), (rect.width * t, rect.height)).color(FILL_COLOR.with_alpha(0.6));
+        let color = if look.focused { Color::YELLOW } else { Color::WHITE };
+        let decimals = if step.fract() == 0.0 { 0 } else { 2 };
+        self.text(&format!("{}: {:.*}", text, decimals, value), rect.x + 8.0, rect.center_y(), TEXT_SIZE, color);
+        changed
+    }
+
+    //one bar per value, `max` is the top of the graph and taller bars are cut off there,
+    //`mark` draws a line across at that value
+    pub fn graph(&mut self, values: impl ExactSizeIterator<Item = f32>, max: f32, mark: Option<f32>) {
+        let rect = self.next_rect(GRAPH_HEIGHT);
+        self.state.cover(rect);
+        self.draw.rect((rect.x, rect.y), (rect.width, rect.height)).color(WIDGET_COLOR);
+        let bar = rect.width / (values.len().max(1) as f32);
+        let bottom = rect.max_y();
+        for (i, value) in values.enumerate() {
+            let height = (value / max).clamp(0.0, 1.0) * rect.height;
+            self.draw.rect((rect.x + (i as f32) * bar, bottom - height), (bar, height)).color(FILL_COLOR);
+        }
+        if let Some(mark) = mark.filter(|mark| *mark <= max) {
+            let y = bottom - (mark / max) * rect.height;
+            self.draw.line((rect.x, y), (rect.max_x(), y)).color(Color::YELLOW).width(1.0);
+        }
+    }
+
+    pub fn text_field(&mut self, text: &str, value: &mut String) -> bool {
+        let rect = self.next_rect(ROW_HEIGHT);
+        let (changed, look) = self.state.text_field(self.input, rect, value);
+        self.draw.rect((rect.x, rect.y), (rect.width, rect.height)).color(look.color());
+        let color = if look.focused { Color::YELLOW } else { Color::WHITE };
+        let cursor = if look.active { "_" } else { "" };
+        self.text(&format!("{}: {}{}", text, value, cursor), rect.x + 8.0, rect.center_y(), TEXT_SIZE, color);
+        changed
+    }
+}
+
+impl Drop for Ui<'_> {
+    fn drop(&mut self) {
+        self.state.end(self.input);
+    }
+}
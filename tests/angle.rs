@@ -0,0 +1,99 @@
+use indifi::*;
+use notan::math::Vec2;
+use proptest::prelude::*;
+
+//angles close to the 0/360 seam, where wrapping bugs show up
+fn near_zero() -> impl Strategy<Value = f32> {
+    prop_oneof![-5.0f32..5.0, 355.0f32..365.0, -365.0f32..-355.0]
+}
+
+fn any_degrees() -> impl Strategy<Value = f32> {
+    prop_oneof![near_zero(), -10000.0f32..10000.0]
+}
+
+proptest! {
+    #[test]
+    fn always_wrapped(degrees in any_degrees()) {
+        let angle = Angle::from_degrees(degrees);
+        prop_assert!((0.0..360.0).contains(&angle.degrees()), "{} wrapped to {}", degrees, angle.degrees());
+    }
+
+    #[test]
+    fn full_turns_change_nothing(degrees in any_degrees(), turns in -5i32..5) {
+        let angle = Angle::from_degrees(degrees);
+        let turned = Angle::from_degrees(degrees + turns as f32 * 360.0);
+        prop_assert!(angle.difference(turned).abs() < 0.01);
+    }
+
+    #[test]
+    fn difference_is_the_short_way(from in any_degrees(), to in any_degrees()) {
+        let (from, to) = (Angle::from_degrees(from), Angle::from_degrees(to));
+        let difference = from.difference(to);
+        prop_assert!((-180.0..180.0).contains(&difference));
+        prop_assert!((from + difference).difference(to).abs() < 0.01);
+    }
+
+    #[test]
+    fn lerp_hits_both_ends(from in any_degrees(), to in any_degrees()) {
+        let (from, to) = (Angle::from_degrees(from), Angle::from_degrees(to));
+        prop_assert!(from.lerp(to, 0.0).difference(from).abs() < 0.01);
+        prop_assert!(from.lerp(to, 1.0).difference(to).abs() < 0.01);
+    }
+
+    #[test]
+    fn lerp_stays_on_the_short_arc(from in near_zero(), to in near_zero(), t in 0.0f32..1.0) {
+        let (from, to) = (Angle::from_degrees(from), Angle::from_degrees(to));
+        let blended = from.lerp(to, t);
+        let total = from.difference(to).abs();
+        prop_assert!(from.difference(blended).abs() <= total + 0.01);
+        prop_assert!(blended.difference(to).abs() <= total + 0.01);
+    }
+
+    #[test]
+    fn rotate_towards_never_overshoots(from in any_degrees(), to in any_degrees(), step in 0.0f32..400.0) {
+        let (from, to) = (Angle::from_degrees(from), Angle::from_degrees(to));
+        let turned = from.rotate_towards(to, step);
+        let before = from.difference(to);
+        let after = turned.difference(to);
+        prop_assert!(after.abs() <= before.abs() + 0.01);
+        //still on the same side of the target, unless it got there
+        prop_assert!(after.abs() < 0.01 || after.signum() == before.signum());
+        prop_assert!(from.difference(turned).abs() <= step + 0.01);
+    }
+
+    #[test]
+    fn rotation_reaches_target_across_zero(start in near_zero(), target in near_zero()) {
+        let mut transform = TransformPR::new();
+        transform.mut_rotation().reset(Angle::from_degrees(start));
+        let target = Angle::from_degrees(target);
+        for _ in 0..60 {
+            transform.update(1.0 / 60.0, Vec2::new(0.0, 0.0), target, Surface::default());
+        }
+        prop_assert!(transform.rotation().difference(target).abs() < 0.01);
+    }
+}
+
+#[test]
+fn between_points_along_the_axes() {
+    let origin = Vec2::new(0.0, 0.0);
+    assert_eq!(Angle::between(origin, Vec2::new(1.0, 0.0)).degrees(), 0.0);
+    assert!((Angle::between(origin, Vec2::new(0.0, 1.0)).degrees() - 90.0).abs() < 0.01);
+    assert!((Angle::between(origin, Vec2::new(0.0, -1.0)).degrees() - 270.0).abs() < 0.01);
+}
+
+#[test]
+fn non_finite_degrees_become_zero() {
+    assert_eq!(Angle::from_degrees(f32::NAN), Angle::ZERO);
+    assert_eq!(Angle::from_degrees(f32::INFINITY), Angle::ZERO);
+}
+
+#[test]
+fn ease_out_slows_down_near_the_target() {
+    let mut linear = TransformPR::new();
+    let mut eased = TransformPR::new().with_turning(150.0, TurnEasing::EaseOut { degrees: 45.0 });
+    let target = Angle::from_degrees(20.0);
+    for transform in [&mut linear, &mut eased] {
+        transform.update(1.0 / 60.0, Vec2::new(0.0, 0.0), target, Surface::default());
+    }
+    assert!(eased.rotation().degrees() < linear.rotation().degrees());
+}
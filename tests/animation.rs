@@ -0,0 +1,51 @@
+use indifi::*;
+
+fn sheet(facing: Facing) -> SpriteSheet {
+    SpriteSheet { facing, ..PLAYER_SHEET }
+}
+
+#[test]
+fn eight_directions_pick_the_closest_row() {
+    let sheet = sheet(Facing::Eight);
+    assert_eq!(sheet.row(Angle::from_degrees(0.0)), (0, 0.0));
+    assert_eq!(sheet.row(Angle::from_degrees(50.0)), (1, 0.0));
+    assert_eq!(sheet.row(Angle::from_degrees(350.0)), (0, 0.0));
+    assert_eq!(sheet.row(Angle::from_degrees(268.0)), (6, 0.0));
+}
+
+#[test]
+fn four_directions_skip_the_diagonals() {
+    let sheet = sheet(Facing::Four);
+    assert_eq!(sheet.row(Angle::from_degrees(40.0)).0, 0);
+    assert_eq!(sheet.row(Angle::from_degrees(50.0)).0, 2);
+    assert_eq!(sheet.row(Angle::from_degrees(300.0)).0, 6);
+}
+
+#[test]
+fn free_facing_turns_the_rest_of_the_way() {
+    let (row, turn) = sheet(Facing::Free).row(Angle::from_degrees(350.0));
+    assert_eq!(row, 0);
+    assert!((turn + 10.0).abs() < 0.01);
+}
+
+#[test]
+fn walking_faster_plays_faster() {
+    let mut slow = Animation::default();
+    let mut fast = Animation::default();
+    slow.advance(0.25, 20.0, 50.0);
+    fast.advance(0.25, 50.0, 50.0);
+    assert!(slow.is_walking() && fast.is_walking());
+    assert!(fast.time() > slow.time());
+    //walk frames come after the idle ones
+    let sheet = sheet(Facing::Eight);
+    assert!(sheet.column(&fast) >= sheet.idle_frames);
+}
+
+#[test]
+fn standing_still_idles() {
+    let mut animation = Animation::default();
+    animation.advance(0.5, 40.0, 50.0);
+    animation.advance(0.5, 0.0, 50.0);
+    assert!(!animation.is_walking());
+    assert!(PLAYER_SHEET.column(&animation) < PLAYER_SHEET.idle_frames);
+}
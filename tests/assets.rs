@@ -0,0 +1,56 @@
+use indifi::*;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("indifi-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn missing_files_fall_back_to_the_embedded_ones() {
+    let dir = temp_dir("assets-missing");
+    let assets = AssetDir::from_dir(&dir);
+    assert!(assets.read(AssetFile::Atlas).is_err());
+    assert_eq!(assets.bytes(AssetFile::Atlas), AssetFile::Atlas.embedded());
+    assert!(AssetDir::embedded().read(AssetFile::Font).is_err());
+}
+
+#[test]
+fn written_files_are_noticed_once() {
+    let dir = temp_dir("assets-changed");
+    std::fs::write(dir.join(AssetFile::Font.file_name()), b"old").unwrap();
+    let mut assets = AssetDir::from_dir(&dir);
+    assert!(assets.changed().is_empty());
+
+    std::thread::sleep(Duration::from_millis(20));
+    std::fs::write(dir.join(AssetFile::Font.file_name()), b"new").unwrap();
+    std::fs::write(dir.join(AssetFile::Atlas.file_name()), b"appeared").unwrap();
+    let mut changed = assets.changed();
+    changed.sort_by_key(|asset| asset.file_name());
+    assert_eq!(changed, vec![AssetFile::Font, AssetFile::Atlas]);
+    assert!(assets.changed().is_empty());
+    assert_eq!(assets.bytes(AssetFile::Font), b"new");
+
+    std::fs::remove_file(dir.join(AssetFile::Font.file_name())).unwrap();
+    assert!(assets.changed().is_empty());
+}
+
+#[test]
+fn a_watched_config_file() {
+    let dir = temp_dir("assets-config");
+    let mut file = WatchedFile::new(dir.join(DEFAULT_CONFIG_PATH));
+    assert!(!file.changed());
+    std::fs::write(file.path(), "[render]\nzoom = 9\n").unwrap();
+    assert!(file.changed());
+    assert!(!file.changed());
+}
+
+#[test]
+fn paths_name_their_asset() {
+    assert_eq!(AssetFile::from_path("art/player_sheet.png"), Some(AssetFile::PlayerSheet));
+    assert_eq!(AssetFile::from_path("src/assets/Ubuntu-B.ttf"), Some(AssetFile::Font));
+    assert_eq!(AssetFile::from_path("src/assets/atlas_test.png"), None);
+}
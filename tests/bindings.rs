@@ -0,0 +1,100 @@
+use indifi::*;
+use notan::input::keyboard::Keyboard;
+use notan::prelude::KeyCode;
+
+//`keys` held down, the last of them pressed this frame
+fn press(keys: &[KeyCode]) -> Keyboard {
+    let mut keyboard = Keyboard::default();
+    for key in keys {
+        keyboard.down.insert(*key, 0.0);
+    }
+    keyboard.pressed.insert(*keys.last().unwrap());
+    keyboard
+}
+
+#[test]
+fn bindings_survive_a_round_trip() {
+    let mut bindings = KeyBindings::default();
+    let ctrl_shift = Modifiers { ctrl: true, shift: true, alt: false };
+    bindings.bind(Action::Screenshot, Binding::new(KeyCode::S).with_modifiers(ctrl_shift));
+    bindings.bind(Action::ZoomIn, Binding::new(KeyCode::LShift));
+    bindings.clear(Action::ToggleDebug);
+    bindings.movement_model = MovementModel::ScreenRelative;
+
+    let parsed = KeyBindings::parse(&bindings.to_text()).unwrap();
+    for action in Action::ALL {
+        assert_eq!(parsed.get(action), bindings.get(action), "{}", action.name());
+    }
+    assert_eq!(parsed.movement_model, MovementModel::ScreenRelative);
+    assert_eq!(parsed.to_text(), bindings.to_text());
+}
+
+#[test]
+fn modifiers_are_written_in_front_of_the_key() {
+    let binding = Binding::parse("alt + Ctrl+shift+f5").unwrap();
+    assert_eq!(binding.key, KeyCode::F5);
+    assert_eq!(binding.modifiers, Modifiers { ctrl: true, shift: true, alt: true });
+    assert_eq!(binding.to_string(), "ctrl+shift+alt+F5");
+    assert_eq!(Binding::parse("P"), Some(Binding::new(KeyCode::P)));
+    assert_eq!(Binding::parse("super+P"), None);
+    assert_eq!(Binding::parse("ctrl+"), None);
+    assert_eq!(Binding::parse("ctrl"), None);
+}
+
+#[test]
+fn unknown_names_are_errors_with_their_line() {
+    let error = |text: &str| KeyBindings::parse(text).err().unwrap();
+    assert_eq!(error("# keys\njump = Space"), "line 2: unknown action `jump`");
+    assert_eq!(error("zoom_in = P, Banana"), "line 1: unknown key `Banana`");
+    assert_eq!(error("zoom_in = meta+P"), "line 1: unknown key `meta+P`");
+    assert_eq!(error("zoom_in P"), "line 1: expected `action = keys`");
+    assert_eq!(error("movement_model = sideways"), "line 1: movement_model is `facing` or `screen`, not `sideways`");
+}
+
+#[test]
+fn pressed_actions_need_exactly_their_modifiers() {
+    let plain = Binding::new(KeyCode::P);
+    let ctrl = Binding::parse("ctrl+P").unwrap();
+    assert!(plain.was_pressed(&press(&[KeyCode::P])));
+    assert!(!plain.was_pressed(&press(&[KeyCode::LControl, KeyCode::P])));
+    assert!(ctrl.was_pressed(&press(&[KeyCode::RControl, KeyCode::P])));
+    assert!(!ctrl.was_pressed(&press(&[KeyCode::P])));
+    //held ones ignore extra modifiers
+    assert!(plain.is_down(&press(&[KeyCode::LShift, KeyCode::P])));
+}
+
+#[test]
+fn a_modifier_can_be_the_key() {
+    let bindings = KeyBindings::parse("zoom_in = LShift\nzoom_out = ctrl+LAlt").unwrap();
+    assert!(bindings.was_pressed(&press(&[KeyCode::LShift]), Action::ZoomIn));
+    assert!(!bindings.was_pressed(&press(&[KeyCode::LControl, KeyCode::LShift]), Action::ZoomIn));
+    assert!(bindings.was_pressed(&press(&[KeyCode::LControl, KeyCode::LAlt]), Action::ZoomOut));
+    assert!(!bindings.was_pressed(&press(&[KeyCode::LAlt]), Action::ZoomOut));
+}
+
+#[test]
+fn a_key_can_not_be_bound_to_two_actions() {
+    assert_eq!(
+        KeyBindings::parse("zoom_in = P, Q\nzoom_out = O, q").err().unwrap(),
+        "line 2: `Q` is already bound to zoom_in"
+    );
+    //with modifiers it is a different binding
+    assert!(KeyBindings::parse("zoom_in = P\nzoom_out = shift+P").is_ok());
+
+    //a default that the file gives to another action goes over to it
+    let bindings = KeyBindings::parse("zoom_in = W").unwrap();
+    assert_eq!(bindings.get(Action::ZoomIn), &[Binding::new(KeyCode::W)]);
+    assert_eq!(bindings.get(Action::MoveForward), &[Binding::new(KeyCode::Up)]);
+    //unless the file lists that action too
+    assert!(KeyBindings::parse("move_forward = W\nzoom_in = W").is_err());
+    assert!(KeyBindings::parse("zoom_in = W\nmove_forward = W").is_err());
+}
+
+#[test]
+fn the_editor_saves_with_f5_unless_rebound() {
+    let bindings = KeyBindings::default();
+    assert!(bindings.was_pressed(&press(&[KeyCode::F5]), Action::Save));
+    let bindings = KeyBindings::parse("save = ctrl+S\n").unwrap();
+    assert!(!bindings.was_pressed(&press(&[KeyCode::F5]), Action::Save));
+    assert!(bindings.was_pressed(&press(&[KeyCode::LControl, KeyCode::S]), Action::Save));
+}
@@ -0,0 +1,58 @@
+use indifi::cli::{ self, Command, PlayArgs, WorldArgs };
+use indifi::*;
+use notan::math::Vec2;
+
+fn parse(args: &[&str]) -> Result<Command, String> {
+    cli::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn no_arguments_play_with_defaults() {
+    assert_eq!(parse(&[]), Ok(Command::Play(PlayArgs::default())));
+}
+
+#[test]
+fn play_options() {
+    let Ok(Command::Play(args)) = parse(
+        &["--seed", "9", "--position", "100,200.5", "--size", "1280x720", "--fullscreen", "--world", "a.world", "--assets", "art"]
+    ) else {
+        panic!("not a play command");
+    };
+    assert_eq!(args.seed, Some(9));
+    assert_eq!(args.position, Some(Vec2::new(100.0, 200.5)));
+    assert_eq!(args.world.as_deref(), Some("a.world"));
+    assert_eq!(args.assets.as_deref(), Some("art"));
+    let mut config = Config::default();
+    for (key, value) in &args.overrides {
+        config.set(key, value).unwrap();
+    }
+    assert_eq!((config.window.width, config.window.height), (1280, 720));
+    assert!(config.window.fullscreen);
+}
+
+#[test]
+fn subcommands() {
+    assert_eq!(
+        parse(&["generate", "out.world", "--seed", "4", "--area-size", "8"]),
+        Ok(Command::Generate {
+            path: "out.world".to_string(),
+            world: WorldArgs { seed: Some(4), area_size: Some(8), ..WorldArgs::default() },
+        })
+    );
+    assert_eq!(
+        parse(&["stats", "--world", "out.world"]),
+        Ok(Command::Stats(WorldArgs { world: Some("out.world".to_string()), ..WorldArgs::default() }))
+    );
+    assert_eq!(parse(&["help"]), Ok(Command::Help));
+}
+
+#[test]
+fn mistakes_name_the_option() {
+    assert!(parse(&["--seed"]).unwrap_err().contains("--seed"));
+    assert!(parse(&["--seed", "many"]).unwrap_err().contains("many"));
+    assert!(parse(&["--size", "big"]).unwrap_err().contains("--size"));
+    assert!(parse(&["--tick-rate", "0"]).is_err());
+    assert!(parse(&["--colour"]).unwrap_err().contains("--colour"));
+    assert!(parse(&["generate"]).is_err());
+    assert!(parse(&["stats", "--name", "x"]).is_err());
+}
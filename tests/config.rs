@@ -0,0 +1,127 @@
+use indifi::*;
+use notan::math::Vec2;
+
+#[test]
+fn reads_sections() {
+    let config = Config::parse(
+        "# tuned for a small laptop\n\
+         [window]\n\
+         width = 1280 # wide\n\
+         fullscreen = true\n\
+         [render]\n\
+         zoom = 9\n\
+         [world]\n\
+         area_size = 4\n\
+         player_start = [512, 600.5]\n"
+    ).unwrap();
+    assert_eq!(config.window.width, 1280);
+    assert!(config.window.fullscreen);
+    assert_eq!(config.window.height, WindowSettings::default().height);
+    assert_eq!(config.render.zoom, 9);
+    assert_eq!(config.world.area_size, 4);
+    assert_eq!(config.world.player_start, Vec2::new(512.0, 600.5));
+    assert_eq!(config.validate(), Ok(()));
+}
+
+#[test]
+fn parse_errors_name_the_line() {
+    let error = Config::parse("[window]\nwidth = wide").unwrap_err();
+    assert!(error.starts_with("line 2:"), "{}", error);
+    let error = Config::parse("[window]\ncolour = 3").unwrap_err();
+    assert!(error.starts_with("line 2:") && error.contains("colour"), "{}", error);
+    assert!(Config::parse("width 3").is_err());
+}
+
+#[test]
+fn set_rejects_unknown_settings_and_wrong_values() {
+    let mut config = Config::default();
+    let error = config.set("window.colour", "3").unwrap_err();
+    assert!(error.contains("window.colour"), "{}", error);
+    assert!(config.set("window.width", "true").is_err());
+    assert!(config.set("window.width", "-1").is_err());
+    assert!(config.set("window.width", "wide").is_err());
+    assert_eq!(config, Config::default());
+}
+
+#[test]
+fn validation_finds_every_problem() {
+    let mut config = Config::default();
+    config.set("render.zoom", "3").unwrap();
+    config.set("render.chunk_texture_size", "1000").unwrap();
+    config.set("world.area_size", "2").unwrap();
+    let error = config.validate().unwrap_err();
+    assert!(error.contains("render.zoom"), "{}", error);
+    assert!(error.contains("chunk_texture_size"), "{}", error);
+    //the default start is outside of a two chunk world
+    assert!(error.contains("player_start"), "{}", error);
+    assert_eq!(error.lines().count(), 3);
+}
+
+#[test]
+fn written_settings_read_back() {
+    let mut config = Config::default();
+    config.set("window.fullscreen", "true").unwrap();
+    config.set("render.zoom", "10").unwrap();
+    config.set("world.player_start", "[100.5, 200]").unwrap();
+    assert_eq!(Config::parse(&config.to_text()), Ok(config));
+}
+
+#[test]
+fn saved_zoom_stays_in_the_limits() {
+    let config = Config::default();
+    assert_eq!(config.clamp_zoom(30), config.render.max_zoom);
+    assert_eq!(config.clamp_zoom(1), config.render.min_zoom);
+}
+
+#[test]
+fn sized_worlds() {
+    let sim = SimulationBuilder::new(3).area_size(4).build();
+    assert_eq!(sim.terrain.area_size(), 4);
+    assert_eq!(sim.terrain.size_in_tiles(), 4 * CHUNK_TILES);
+    assert!(sim.terrain.chunk((3, 3)).is_some());
+    assert!(sim.terrain.chunk((4, 0)).is_none());
+}
+
+fn changes(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+}
+
+#[test]
+fn edits_keep_comments_and_other_lines() {
+    let text = "# my settings\n[window]\n  width = 800 # fits the laptop\nheight = 600\n\n[world]\narea_size = 8\n";
+    let edited = Config::edit_text(text, &changes(&[("window.width", "1024"), ("world.area_size", "16")])).unwrap();
+    assert_eq!(
+        edited,
+        "# my settings\n[window]\n  width = 1024 # fits the laptop\nheight = 600\n\n[world]\narea_size = 16\n"
+    );
+    assert_eq!(Config::edit_text(text, &[]).unwrap(), text);
+}
+
+#[test]
+fn edits_add_missing_settings_to_their_section() {
+    let text = "[window]\nwidth = 800\n\n[world]\narea_size = 8\n";
+    let edited = Config::edit_text(
+        text,
+        &changes(&[("window.fullscreen", "true"), ("render.zoom", "10"), ("render.min_zoom", "6")])
+    ).unwrap();
+    assert_eq!(
+        edited,
+        "[window]\nwidth = 800\nfullscreen = true\n\n[world]\narea_size = 8\n\n[render]\nzoom = 10\nmin_zoom = 6\n"
+    );
+    let config = Config::parse(&edited).unwrap();
+    assert!(config.window.fullscreen);
+    assert_eq!((config.render.zoom, config.render.min_zoom, config.world.area_size), (10, 6, 8));
+
+    //an empty or missing file gets only the changes
+    assert_eq!(Config::edit_text("", &changes(&[("world.area_size", "4")])).unwrap(), "[world]\narea_size = 4\n");
+    //a section without settings yet
+    let edited = Config::edit_text("[window]\n", &changes(&[("window.vsync", "false")])).unwrap();
+    assert_eq!(edited, "[window]\nvsync = false\n");
+}
+
+#[test]
+fn edits_only_touch_the_named_section() {
+    let text = "[render]\nzoom = 9\n[window]\nzoom_note = 1 # not a setting of render\n";
+    let edited = Config::edit_text(text, &changes(&[("render.zoom", "11")])).unwrap();
+    assert_eq!(edited, "[render]\nzoom = 11\n[window]\nzoom_note = 1 # not a setting of render\n");
+}
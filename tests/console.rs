@@ -0,0 +1,89 @@
+use indifi::*;
+
+#[derive(Default)]
+struct Counter {
+    count: i32,
+    step: i32,
+}
+
+fn registry() -> CommandRegistry<Counter> {
+    CommandRegistry::new()
+        .command("add", "<n>", "adds n to the count", |counter: &mut Counter, args| {
+            counter.count += parse_arg::<i32>(args, 0, "n")?;
+            Ok(counter.count.to_string())
+        })
+        .command("bump", "", "adds the step", |counter, _| {
+            counter.count += counter.step;
+            Ok(String::new())
+        })
+        .variable(
+            "step",
+            "what bump adds",
+            |counter| counter.step.to_string(),
+            |counter, value| {
+                counter.step = value.parse().map_err(|_| "not a number")?;
+                Ok(())
+            }
+        )
+}
+
+#[test]
+fn commands_and_variables_run_by_name() {
+    let registry = registry();
+    let mut counter = Counter::default();
+    assert_eq!(registry.run(&mut counter, "add 3"), Ok("3".to_string()));
+    assert_eq!(registry.run(&mut counter, "set step 2"), Ok("step = 2".to_string()));
+    assert_eq!(registry.run(&mut counter, "step 5"), Ok("step = 5".to_string()));
+    assert_eq!(registry.run(&mut counter, "step"), Ok("step = 5".to_string()));
+    registry.run(&mut counter, "bump").unwrap();
+    assert_eq!(counter.count, 8);
+
+    let error = registry.run(&mut counter, "add three").unwrap_err();
+    assert!(error.contains("<n>"), "{}", error);
+    assert!(registry.run(&mut counter, "add").is_err());
+    assert!(registry.run(&mut counter, "step lots").is_err());
+    assert!(registry.run(&mut counter, "jump").is_err());
+    assert_eq!(counter.count, 8);
+    assert!(registry.run(&mut counter, "help").unwrap().contains("add <n>"));
+    assert!(registry.run(&mut counter, "help nothing").is_err());
+}
+
+#[test]
+fn later_commands_replace_earlier_ones() {
+    let registry = registry().command("add", "", "does nothing", |_, _| Ok("no".to_string()));
+    assert_eq!(registry.commands().count(), 2);
+    assert_eq!(registry.run(&mut Counter::default(), "add 1"), Ok("no".to_string()));
+}
+
+#[test]
+fn quotes_keep_spaces() {
+    assert_eq!(split_words("  save  \"my world.world\" x"), Ok(vec![
+        "save".to_string(),
+        "my world.world".to_string(),
+        "x".to_string(),
+    ]));
+    assert_eq!(split_words("name \"\""), Ok(vec!["name".to_string(), String::new()]));
+    assert!(split_words("save \"open").is_err());
+}
+
+#[test]
+fn the_console_remembers_what_was_entered() {
+    let registry = registry();
+    let mut counter = Counter::default();
+    let mut console = Console::default();
+    for line in ["add 1", "add 2", "add 2", "jump"] {
+        console.type_text(line);
+        console.submit(&registry, &mut counter);
+    }
+    assert_eq!(console.history(), ["add 1", "add 2", "jump"]);
+    assert_eq!(console.output().last(), Some("error: unknown command `jump`, `help` lists them"));
+    assert_eq!(counter.count, 5);
+
+    console.history_up();
+    console.history_up();
+    assert_eq!(console.input(), "add 2");
+    console.history_down();
+    assert_eq!(console.input(), "jump");
+    console.history_down();
+    assert_eq!(console.input(), "");
+}
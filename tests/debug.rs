@@ -0,0 +1,21 @@
+use indifi::*;
+
+#[test]
+fn frame_times_keep_the_latest() {
+    let mut frames = FrameTimes::new(3);
+    assert_eq!(frames.average(), 0.0);
+    for ms in [40.0, 10.0, 20.0, 30.0] {
+        frames.push(ms);
+    }
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames.iter().collect::<Vec<_>>(), [10.0, 20.0, 30.0]);
+    assert_eq!(frames.max(), 30.0);
+    assert_eq!(frames.average(), 20.0);
+}
+
+#[test]
+fn bytes_get_units() {
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(1536), "1.5 KiB");
+    assert_eq!(format_bytes(64 * 1024 * 1024), "64.0 MiB");
+}
@@ -0,0 +1,82 @@
+use indifi::*;
+use notan::math::Vec2;
+
+#[test]
+fn despawned_ids_are_reused_last_freed_first() {
+    let mut world = World::new();
+    let a = world.spawn();
+    let b = world.spawn();
+    let c = world.spawn();
+    assert_eq!((a, b, c), (0, 1, 2));
+
+    world.despawn(a);
+    world.despawn(c);
+    //despawning twice does not free the id twice
+    world.despawn(c);
+    assert!(!world.is_alive(a) && world.is_alive(b) && !world.is_alive(c));
+    assert_eq!(world.entities().collect::<Vec<_>>(), vec![b]);
+
+    assert_eq!(world.spawn(), c);
+    assert_eq!(world.spawn(), a);
+    assert_eq!(world.spawn(), 3);
+    assert_eq!(world.entities().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    assert!(!world.is_alive(4));
+}
+
+#[test]
+fn despawn_takes_every_component_with_it() {
+    let mut world = World::new();
+    let npc = NpcBuilder::new(Behaviour::wander(Vec2::new(40.0, 40.0)))
+        .position(40.0, 40.0)
+        .spawn(&mut world);
+    let player = PlayerBuilder::new().position(60.0, 40.0).spawn(&mut world);
+    world.networked.insert(player, Networked { id: 3 });
+    spatial_system(&mut world);
+    assert!(world.behaviours.get(npc).is_some() && world.players.get(player).is_some());
+    assert_eq!(world.spatial.position(player), Some(Vec2::new(60.0, 40.0)));
+
+    for entity in [npc, player] {
+        world.despawn(entity);
+        assert!(world.transforms.get(entity).is_none());
+        assert!(world.intents.get(entity).is_none());
+        assert!(world.renderables.get(entity).is_none());
+        assert!(world.inputs.get(entity).is_none());
+        assert!(world.colliders.get(entity).is_none());
+        assert!(world.behaviours.get(entity).is_none());
+        assert!(world.players.get(entity).is_none());
+        assert!(world.networked.get(entity).is_none());
+        assert_eq!(world.spatial.position(entity), None);
+    }
+    assert!(world.spatial.query_radius(Vec2::new(50.0, 40.0), 50.0).is_empty());
+
+    //a reused id starts without the components of the entity that had it before
+    let reused = world.spawn();
+    assert_eq!(reused, player);
+    assert!(world.transforms.get(reused).is_none() && world.players.get(reused).is_none());
+}
+
+#[test]
+fn storage_iterates_over_filled_slots_only() {
+    let mut storage = Storage::<u32>::default();
+    storage.insert(5, 50);
+    storage.insert(0, 0);
+    storage.insert(2, 20);
+    storage.insert(3, 30);
+    assert_eq!(storage.remove(3), Some(30));
+    assert_eq!(storage.remove(3), None);
+    assert_eq!(storage.remove(9), None);
+    assert_eq!(storage.iter().collect::<Vec<_>>(), vec![(0, &0), (2, &20), (5, &50)]);
+
+    for (entity, item) in storage.iter_mut() {
+        *item += entity as u32;
+    }
+    assert_eq!(storage.iter().map(|(_, item)| *item).collect::<Vec<_>>(), vec![0, 22, 55]);
+    assert_eq!(storage.get(1), None);
+    assert_eq!(storage.get(6), None);
+    assert_eq!(storage.get_mut(5), Some(&mut 55));
+
+    //inserting over an item replaces it
+    storage.insert(2, 7);
+    assert_eq!(storage.get(2), Some(&7));
+    assert_eq!(storage.iter().count(), 3);
+}
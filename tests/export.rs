@@ -0,0 +1,125 @@
+use indifi::*;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("indifi-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+//a w x h image where every pixel holds its own coordinates
+fn numbered(width: u32, height: u32) -> PixelImage {
+    let pixels = (0..height).flat_map(|y| (0..width).flat_map(move |x| [x as u8, y as u8, 0, 255])).collect();
+    PixelImage::from_pixels(width, height, pixels, false).unwrap()
+}
+
+#[test]
+fn images_read_back_bottom_up_are_turned_over() {
+    let top_down = numbered(3, 3);
+    let mut rows: Vec<u8> = top_down.pixels().chunks(3 * 4).rev().flatten().copied().collect();
+    assert_eq!(PixelImage::from_pixels(3, 3, rows.clone(), true), Ok(top_down));
+    rows.pop();
+    assert!(PixelImage::from_pixels(3, 3, rows, false).is_err());
+}
+
+#[test]
+fn blitting_cuts_off_what_sticks_out() {
+    let mut image = PixelImage::new(4, 4);
+    image.blit(&numbered(3, 3), 2, 1);
+    assert_eq!(image.pixel(2, 1), Some([0, 0, 0, 255]));
+    assert_eq!(image.pixel(3, 3), Some([1, 2, 0, 255]));
+    assert_eq!(image.pixel(1, 1), Some([0, 0, 0, 0]));
+    assert_eq!(image.pixel(4, 0), None);
+    image.blit(&numbered(2, 2), 4, 0);
+}
+
+#[test]
+fn pngs_decode_to_the_same_pixels() {
+    let image = numbered(5, 2);
+    let dir = temp_dir("export-png");
+    let path = next_free_path(&dir, "shot");
+    assert_eq!(path, dir.join("shot-0001.png"));
+    image.save_png(&path).unwrap();
+    assert_eq!(next_free_path(&dir, "shot"), dir.join("shot-0002.png"));
+
+    let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (5, 2));
+    assert_eq!(pixels, image.pixels());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn exports_lay_chunks_out_in_a_grid() {
+    let terrain = Terrain::generate_sized(1, 4);
+    let settings = RenderSettings::default();
+    let export = WorldExport::new(&terrain, &settings, 2, (3, 2), (1, 1)).unwrap();
+    assert_eq!((export.from, export.to), ((1, 1), (3, 2)));
+    assert_eq!(export.chunk_pixels, settings.chunk_texture_size / 4);
+    assert_eq!(export.size(), (3 * export.chunk_pixels, 2 * export.chunk_pixels));
+    assert_eq!(export.chunks().count(), 6);
+    assert_eq!(export.chunks().nth(3), Some((1, 2)));
+    assert_eq!(export.offset((2, 2)), (export.chunk_pixels, export.chunk_pixels));
+
+    assert!(WorldExport::new(&terrain, &settings, 0, (0, 0), (4, 0)).is_err());
+    assert!(WorldExport::new(&terrain, &settings, 5, (0, 0), (1, 1)).is_err());
+    let big = Terrain::generate_sized(1, 32);
+    let error = WorldExport::whole(&big, &settings, 0).unwrap_err();
+    assert!(error.contains("32768x32768"), "{}", error);
+    assert!(WorldExport::whole(&big, &settings, 1).is_ok());
+}
+
+fn decode_png(path: &std::path::Path) -> (u32, u32, Vec<u8>) {
+    let decoder = png::Decoder::new(std::fs::File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    (info.width, info.height, pixels)
+}
+
+#[test]
+fn exports_are_written_a_row_of_chunks_at_a_time() {
+    //three by two chunks of two pixels, every chunk filled with its own number
+    let export = WorldExport { from: (4, 7), to: (6, 8), lod: 0, chunk_pixels: 2 };
+    let chunk_image = |(x, y): (usize, usize)| {
+        let pixels = [x as u8, y as u8, 0, 255].repeat(4);
+        PixelImage::from_pixels(2, 2, pixels, false).unwrap()
+    };
+    let dir = temp_dir("export-strips");
+    let path = dir.join("world.png");
+    let mut writer = ExportWriter::create(export, &path).unwrap();
+    let mut expected = PixelImage::new(6, 4);
+    while let Some(coords) = writer.next_chunk() {
+        //a chunk that could not be rendered stays transparent
+        let image = (coords != (5, 8)).then(|| chunk_image(coords));
+        if let Some(image) = &image {
+            let (x, y) = export.offset(coords);
+            expected.blit(image, x, y);
+        }
+        writer.add_chunk(image.as_ref()).unwrap();
+    }
+    assert_eq!(writer.progress(), (6, 6));
+    assert!(writer.add_chunk(None).is_err());
+    assert_eq!(writer.finish().unwrap(), path);
+
+    let (width, height, pixels) = decode_png(&path);
+    assert_eq!((width, height), (6, 4));
+    assert_eq!(pixels, expected.pixels());
+    assert_eq!(expected.pixel(5, 3), Some([6, 8, 0, 255]));
+    assert_eq!(expected.pixel(3, 3), Some([0, 0, 0, 0]));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unfinished_exports_say_how_far_they_got() {
+    let export = WorldExport { from: (0, 0), to: (1, 1), lod: 0, chunk_pixels: 2 };
+    let dir = temp_dir("export-unfinished");
+    let mut writer = ExportWriter::create(export, dir.join("world.png")).unwrap();
+    writer.add_chunk(None).unwrap();
+    assert_eq!(writer.next_chunk(), Some((1, 0)));
+    let error = writer.finish().unwrap_err();
+    assert_eq!(error.to_string(), "only 1 of 4 chunks are in the export");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,50 @@
+use indifi::*;
+use notan::math::Vec2;
+
+fn close(a: Vec2, b: Vec2) -> bool {
+    a.distance(b) < 0.0001
+}
+
+#[test]
+fn deadzone_ignores_small_pushes_and_starts_smoothly_at_its_edge() {
+    assert_eq!(apply_deadzone(Vec2::new(0.1, 0.0), 0.2), Vec2::new(0.0, 0.0));
+    assert_eq!(apply_deadzone(Vec2::new(0.2, 0.0), 0.2), Vec2::new(0.0, 0.0));
+    assert!(close(apply_deadzone(Vec2::new(0.0, 0.6), 0.2), Vec2::new(0.0, 0.5)));
+    assert!(close(apply_deadzone(Vec2::new(1.0, 0.0), 0.2), Vec2::new(1.0, 0.0)));
+    //a stick past its rim is still full speed and keeps its direction
+    assert!(close(apply_deadzone(Vec2::new(-2.0, 0.0), 0.2), Vec2::new(-1.0, 0.0)));
+    assert!(close(apply_deadzone(Vec2::new(0.3, 0.4), 0.0), Vec2::new(0.3, 0.4)));
+}
+
+#[test]
+fn sticks_turn_into_movement_and_aim() {
+    let settings = GamepadSettings { sensitivity: 2.0, ..Default::default() };
+    let state = GamepadState { left_stick: Vec2::new(0.0, 0.9), right_stick: Vec2::new(0.1, 0.1) };
+    //up on the stick walks up the screen, the sensitivity can not make it faster than full speed
+    assert!(close(settings.movement(&state), Vec2::new(0.0, -1.0)));
+    assert_eq!(settings.aim(&state), None);
+    let state = GamepadState { right_stick: Vec2::new(0.0, -0.5), ..state };
+    assert!(close(settings.aim(&state).unwrap(), Vec2::new(0.0, 1.0)));
+}
+
+#[test]
+fn settings_are_read_from_the_bindings_file() {
+    let bindings = KeyBindings::parse(
+        "gamepad_deadzone = 0.25\ngamepad_aim_deadzone = 0\ngamepad_sensitivity = 1.5\n"
+    ).unwrap();
+    assert_eq!(bindings.gamepad, GamepadSettings { deadzone: 0.25, aim_deadzone: 0.0, sensitivity: 1.5 });
+    assert_eq!(KeyBindings::parse("").unwrap().gamepad, GamepadSettings::default());
+
+    let round_trip = KeyBindings::parse(&bindings.to_text()).unwrap();
+    assert_eq!(round_trip.gamepad, bindings.gamepad);
+}
+
+#[test]
+fn bad_settings_are_rejected() {
+    let error = |text: &str| KeyBindings::parse(text).err().unwrap();
+    assert_eq!(error("gamepad_deadzone = -0.1"), "line 1: `-0.1` is not a number of 0 or more");
+    assert_eq!(error("gamepad_sensitivity = fast"), "line 1: `fast` is not a number of 0 or more");
+    assert_eq!(error("\ngamepad_aim_deadzone = 1"), "line 2: gamepad_aim_deadzone has to be below 1");
+    //only the deadzones have to stay below 1
+    assert!(KeyBindings::parse("gamepad_sensitivity = 3").is_ok());
+}
@@ -0,0 +1,94 @@
+use indifi::net::*;
+use indifi::*;
+use notan::prelude::Color;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+fn welcome(area_size: u16) -> Vec<u8> {
+    ServerMessage::Welcome { id: 1, seed: 1234, area_size }.encode()
+}
+
+#[test]
+fn welcome_survives_a_round_trip() {
+    let Some(ServerMessage::Welcome { id, seed, area_size }) = ServerMessage::decode(&welcome(16)) else {
+        panic!("not a welcome");
+    };
+    assert_eq!((id, seed, area_size), (1, 1234, 16));
+}
+
+#[test]
+fn welcome_with_an_impossible_area_size_is_rejected() {
+    assert!(ServerMessage::decode(&welcome(0)).is_none());
+    assert!(ServerMessage::decode(&welcome((indifi::MAX_AREA_SIZE + 1) as u16)).is_none());
+    assert!(ServerMessage::decode(&welcome(u16::MAX)).is_none());
+}
+
+fn player(id: u16, display_name: String) -> PlayerSnapshot {
+    PlayerSnapshot {
+        id,
+        display_name,
+        x: 1.0,
+        y: 2.0,
+        degrees: 3.0,
+        vx: 0.0,
+        vy: 0.0,
+        color: [1.0, 1.0, 1.0],
+        last_input: 0,
+    }
+}
+
+#[test]
+fn snapshots_too_big_for_a_packet_are_split() {
+    let small = ServerMessage::snapshots(7, vec![player(1, "a".to_string())]);
+    assert!(matches!(small.as_slice(), [ServerMessage::Snapshot { part: 0, parts: 1, .. }]));
+
+    //longest names, about 290 bytes a player
+    let players = (0..500).map(|id| player(id, "x".repeat(300))).collect();
+    let parts = ServerMessage::snapshots(7, players);
+    assert_eq!(parts.len(), 3);
+    let mut ids = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        let bytes = part.encode();
+        assert!(bytes.len() <= 65507);
+        let Some(ServerMessage::Snapshot { tick, part, parts, players }) = ServerMessage::decode(&bytes) else {
+            panic!("not a snapshot");
+        };
+        assert_eq!((tick, part as usize, parts), (7, i, 3));
+        assert!(players.iter().all(|player| player.display_name.len() == 255));
+        ids.extend(players.iter().map(|player| player.id));
+    }
+    assert_eq!(ids, (0..500).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_split_snapshot_is_applied_once_every_part_is_there() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    server.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+    let mut net = NetClient::connect(server.local_addr().unwrap(), "me", Color::RED).unwrap();
+    let mut sim = SimulationBuilder::new(1).build();
+    let local = sim.player();
+    let mut buffer = vec![0; 65536];
+    let (_, client) = server.recv_from(&mut buffer).unwrap();
+    let send = |message: ServerMessage| {
+        server.send_to(&message.encode(), client).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    let remotes = |sim: &Simulation| sim.world.networked.iter().count();
+    send(ServerMessage::Welcome { id: 1, seed: 1, area_size: 16 });
+    net.poll(&mut sim.world, &sim.terrain, local).unwrap();
+
+    let players = (2..6).map(|id| player(id, format!("p{}", id)));
+    let part = |part: u16, players: Vec<PlayerSnapshot>| ServerMessage::Snapshot { tick: 1, part, parts: 2, players };
+    let (first, second): (Vec<_>, Vec<_>) = players.partition(|player| player.id < 4);
+    send(part(1, second));
+    net.poll(&mut sim.world, &sim.terrain, local).unwrap();
+    assert_eq!(remotes(&sim), 0);
+    send(part(0, first));
+    net.poll(&mut sim.world, &sim.terrain, local).unwrap();
+    assert_eq!(remotes(&sim), 4);
+
+    //a part of a snapshot that never completes changes nothing
+    send(ServerMessage::Snapshot { tick: 2, part: 0, parts: 2, players: vec![player(2, "p2".to_string())] });
+    net.poll(&mut sim.world, &sim.terrain, local).unwrap();
+    assert_eq!(remotes(&sim), 4);
+}
@@ -0,0 +1,103 @@
+use indifi::*;
+use notan::math::Vec2;
+
+//a one chunk world of plain floor with the player standing still at `player` and one npc
+fn world_with(npc: NpcBuilder, player: Vec2) -> (Simulation, Entity) {
+    let mut sim = SimulationBuilder::new(1)
+        .area_size(1)
+        .npc(npc)
+        .player(PlayerBuilder::new().position(player.x, player.y))
+        .build();
+    for y in 0..CHUNK_TILES {
+        for x in 0..CHUNK_TILES {
+            sim.terrain.set_tile((x, y), 0);
+        }
+    }
+    let npc = sim.world.behaviours.iter().map(|(entity, _)| entity).next().unwrap();
+    (sim, npc)
+}
+
+fn npc_pos(sim: &Simulation, npc: Entity) -> Vec2 {
+    sim.world.transforms.get(npc).unwrap().position().vec()
+}
+
+fn tick_for(sim: &mut Simulation, seconds: f32) {
+    for _ in 0..(seconds * sim.tick_rate()) as u32 {
+        sim.tick();
+    }
+}
+
+#[test]
+fn patrol_walks_from_waypoint_to_waypoint() {
+    let waypoints = vec![Vec2::new(150.0, 50.0), Vec2::new(150.0, 150.0)];
+    let npc = NpcBuilder::new(Behaviour::patrol(waypoints.clone())).position(50.0, 50.0);
+    let (mut sim, npc) = world_with(npc, Vec2::new(20.0, 230.0));
+    let next = |sim: &Simulation| match sim.world.behaviours.get(npc) {
+        Some(Behaviour::Patrol { next, .. }) => *next,
+        _ => panic!("not patrolling"),
+    };
+
+    //100 units at 30 a second with time to turn, it only moves on once it is at the waypoint
+    for (waypoint, then) in [(0, 1), (1, 0)] {
+        for _ in 0..60 * 6 {
+            sim.tick();
+            if next(&sim) != waypoint {
+                break;
+            }
+        }
+        assert_eq!(next(&sim), then);
+        assert!(npc_pos(&sim, npc).distance(waypoints[waypoint]) < 3.0);
+    }
+}
+
+#[test]
+fn follow_closes_to_its_distance_and_stops() {
+    let player = Vec2::new(128.0, 128.0);
+    let npc = NpcBuilder::new(Behaviour::Follow { distance: 40.0 }).position(228.0, 128.0);
+    let (mut sim, npc) = world_with(npc, player);
+
+    tick_for(&mut sim, 8.0);
+    let distance = npc_pos(&sim, npc).distance(player);
+    assert!(distance <= 42.0);
+    //stops around the distance instead of walking into the player
+    assert!(distance > 30.0);
+    let stopped = npc_pos(&sim, npc);
+    tick_for(&mut sim, 2.0);
+    assert!(npc_pos(&sim, npc).distance(stopped) < 1.0);
+}
+
+#[test]
+fn flee_moves_away_until_it_is_far_enough() {
+    let player = Vec2::new(80.0, 128.0);
+    let npc = NpcBuilder::new(Behaviour::Flee { distance: 80.0 }).position(100.0, 128.0);
+    let (mut sim, npc) = world_with(npc, player);
+
+    tick_for(&mut sim, 8.0);
+    let pos = npc_pos(&sim, npc);
+    assert!(pos.distance(player) >= 78.0);
+    //straight away from the player
+    assert!(pos.x > 100.0);
+    assert!((pos.y - 128.0).abs() < 4.0);
+
+    //a player out of sight does not scare it
+    let far = NpcBuilder::new(Behaviour::Flee { distance: 80.0 }).position(228.0, 128.0);
+    let (mut sim, npc) = world_with(far, Vec2::new(20.0, 128.0));
+    tick_for(&mut sim, 4.0);
+    assert!(npc_pos(&sim, npc).distance(Vec2::new(228.0, 128.0)) < 1.0);
+}
+
+#[test]
+fn wander_stays_near_its_home() {
+    let home = Vec2::new(128.0, 128.0);
+    let npc = NpcBuilder::new(Behaviour::wander(home)).position(home.x, home.y);
+    let (mut sim, npc) = world_with(npc, Vec2::new(10.0, 10.0));
+
+    let mut farthest: f32 = 0.0;
+    for _ in 0..60 * 60 {
+        sim.tick();
+        farthest = farthest.max(npc_pos(&sim, npc).distance(home));
+    }
+    //wander targets are within 96 units of home, it can only overshoot them by a little
+    assert!(farthest <= 100.0);
+    assert!(farthest > 10.0);
+}
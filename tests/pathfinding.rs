@@ -0,0 +1,71 @@
+use indifi::pathfinding::*;
+use std::cell::Cell;
+
+//a grid from rows of text, `#` are walls
+fn grid<'a>(rows: &'a [&str]) -> (impl Fn((usize, usize)) -> bool + 'a, (usize, usize)) {
+    let blocked = move |(x, y): (usize, usize)| rows[y].as_bytes()[x] == b'#';
+    (blocked, (rows[0].len(), rows.len()))
+}
+
+#[test]
+fn walks_around_a_wall() {
+    let rows = [
+        ".......",
+        "...#...",
+        "...#...",
+        "...#...",
+        ".......",
+    ];
+    let (blocked, size) = grid(&rows);
+    let path = find_path((1, 2), (5, 2), size, &blocked).unwrap();
+    assert_eq!(path.last(), Some(&(5, 2)));
+    assert!(!path.contains(&(1, 2)));
+    assert!(path.iter().all(|tile| !blocked(*tile)));
+    //every step goes to a neighbour and never cuts the corner of a wall
+    let mut previous: (usize, usize) = (1, 2);
+    for &tile in &path {
+        assert!(previous.0.abs_diff(tile.0) <= 1 && previous.1.abs_diff(tile.1) <= 1);
+        assert!(!blocked((tile.0, previous.1)) && !blocked((previous.0, tile.1)));
+        previous = tile;
+    }
+    //over or under the wall, two diagonals and four straight steps either way
+    assert_eq!(path.len(), 6);
+}
+
+#[test]
+fn straight_line_without_walls() {
+    let rows = ["....."];
+    let (blocked, size) = grid(&rows);
+    assert_eq!(find_path((0, 0), (4, 0), size, &blocked), Some(vec![(1, 0), (2, 0), (3, 0), (4, 0)]));
+}
+
+#[test]
+fn unreachable_goals_have_no_path() {
+    let rows = [
+        ".....",
+        "..###",
+        "..#..",
+        "..###",
+    ];
+    let (blocked, size) = grid(&rows);
+    assert_eq!(find_path((0, 0), (4, 2), size, &blocked), None);
+    //a wall or a tile outside the grid
+    assert_eq!(find_path((0, 0), (2, 2), size, &blocked), None);
+    assert_eq!(find_path((0, 0), (5, 0), size, &blocked), None);
+    assert_eq!(find_path((9, 9), (0, 0), size, &blocked), None);
+}
+
+#[test]
+fn gives_up_on_huge_searches() {
+    //a goal boxed in by walls in the middle of a big open grid
+    let size = (2000, 2000);
+    let goal = (1500, 1500);
+    let checked = Cell::new(0usize);
+    let blocked = |tile: (usize, usize)| {
+        checked.set(checked.get() + 1);
+        tile != goal && tile.0.abs_diff(goal.0) <= 1 && tile.1.abs_diff(goal.1) <= 1
+    };
+    assert_eq!(find_path((0, 0), goal, size, blocked), None);
+    //each expanded tile checks its neighbours and the corners of diagonal steps
+    assert!(checked.get() <= MAX_EXPANDED_TILES * 16 + 1);
+}
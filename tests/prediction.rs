@@ -0,0 +1,175 @@
+use indifi::net::*;
+use indifi::server::Server;
+use indifi::*;
+use notan::math::Vec2;
+use notan::prelude::Color;
+use std::time::{ Duration, Instant };
+
+const FRAME: Duration = Duration::from_millis(5);
+const SEED: u64 = 5;
+
+struct Peer {
+    net: NetClient,
+    sim: Simulation,
+}
+
+impl Peer {
+    fn pos(&self) -> Vec2 {
+        self.sim.player_pos()
+    }
+
+    fn remote_pos(&self, id: u16) -> Option<Vec2> {
+        self.sim.world.networked
+            .iter()
+            .find(|(_, networked)| networked.id == id)
+            .and_then(|(entity, _)| self.sim.world.transforms.get(entity))
+            .map(|transform| transform.position().vec())
+    }
+}
+
+//a server and clients talking through a LatencyProxy, all stepped from one thread in real time
+struct Harness {
+    server: Server,
+    proxy: LatencyProxy,
+    peers: Vec<Peer>,
+    next_server_tick: Instant,
+}
+
+impl Harness {
+    fn new(clients: usize, latency_ms: u64, jitter_ms: u64, loss: f32) -> Self {
+        let server = Server::bind("127.0.0.1:0", SEED).unwrap();
+        let proxy = LatencyProxy::bind(
+            "127.0.0.1:0",
+            server.local_addr().unwrap(),
+            Duration::from_millis(latency_ms),
+            Duration::from_millis(jitter_ms),
+            loss
+        ).unwrap();
+        let peers = (0..clients)
+            .map(|i| {
+                let sim = SimulationBuilder::new(0).tick_rate(TICK_RATE).build();
+                let name = format!("peer{}", i);
+                let net = NetClient::connect(proxy.local_addr().unwrap(), &name, Color::RED).unwrap();
+                Peer { net, sim }
+            })
+            .collect();
+        let mut harness = Harness { server, proxy, peers, next_server_tick: Instant::now() };
+        //hellos and welcomes can be dropped too, wait for everyone and then a bit more to settle
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while harness.peers.iter().any(|peer| peer.net.id().is_none()) {
+            assert!(Instant::now() < deadline, "clients never joined");
+            harness.run(Duration::from_millis(100), idle, |_| {});
+        }
+        harness.run(Duration::from_millis(500), idle, |_| {});
+        harness
+    }
+
+    //`input` gives each peer's movement, `observe` runs after every frame
+    fn run(
+        &mut self,
+        duration: Duration,
+        input: impl Fn(usize) -> Vec2,
+        mut observe: impl FnMut(&Harness)
+    ) {
+        let end = Instant::now() + duration;
+        let mut last_frame = Instant::now();
+        while Instant::now() < end {
+            std::thread::sleep(FRAME);
+            let delta = last_frame.elapsed().as_secs_f32();
+            last_frame = Instant::now();
+
+            self.proxy.pump().unwrap();
+            if Instant::now() >= self.next_server_tick {
+                self.next_server_tick += Duration::from_secs_f32(TICK_DELTA);
+                self.server.receive().unwrap();
+                self.server.step();
+                self.server.broadcast();
+            }
+            for (i, peer) in self.peers.iter_mut().enumerate() {
+                let player = peer.sim.player();
+                peer.sim.apply_input(&(Input { movement: input(i), ..Default::default() }));
+                for _ in 0..peer.sim.advance(delta) {
+                    peer.sim.tick();
+                    peer.net.tick(&peer.sim.world, player).unwrap();
+                }
+                peer.net.poll(&mut peer.sim.world, &peer.sim.terrain, player).unwrap();
+                if let Some(seed) = peer.net.seed().filter(|seed| *seed != peer.sim.seed()) {
+                    peer.sim.reseed(seed);
+                }
+                peer.net.interpolate(&mut peer.sim.world);
+            }
+            observe(self);
+        }
+    }
+
+    fn server_pos(&self, id: u16) -> Vec2 {
+        let world = self.server.world();
+        world.players
+            .iter()
+            .find(|(_, player)| player.id() == id)
+            .and_then(|(entity, _)| world.transforms.get(entity))
+            .map(|transform| transform.position().vec())
+            .unwrap()
+    }
+}
+
+fn forward(_: usize) -> Vec2 {
+    Vec2::new(0.0, -1.0)
+}
+
+fn idle(_: usize) -> Vec2 {
+    Vec2::new(0.0, 0.0)
+}
+
+#[test]
+fn prediction_runs_ahead_and_reconciles_with_latency() {
+    let mut harness = Harness::new(1, 100, 20, 0.0);
+    let id = harness.peers[0].net.id().unwrap();
+    let start = harness.peers[0].pos();
+
+    harness.run(Duration::from_millis(1000), forward, |_| {});
+    let predicted = harness.peers[0].pos();
+    //the client moves right away, the server only sees inputs a round trip later
+    assert!(predicted.x > start.x + 20.0);
+    assert!(predicted.x > harness.server_pos(id).x);
+
+    harness.run(Duration::from_millis(600), idle, |_| {});
+    let peer = &harness.peers[0];
+    //only about a round trip of inputs is ever waiting for an acknowledgement
+    assert!(peer.net.pending_inputs() <= ((0.3 / TICK_DELTA) as usize));
+    assert!(peer.pos().distance(harness.server_pos(id)) < 0.01);
+}
+
+#[test]
+fn prediction_recovers_from_packet_loss() {
+    let mut harness = Harness::new(1, 60, 30, 0.2);
+    let id = harness.peers[0].net.id().unwrap();
+
+    harness.run(Duration::from_millis(1000), forward, |_| {});
+    harness.run(Duration::from_millis(1500), idle, |_| {});
+    let peer = &harness.peers[0];
+    assert!(peer.pos().distance(harness.server_pos(id)) < 0.01);
+}
+
+#[test]
+fn remote_players_are_interpolated_between_snapshots() {
+    let mut harness = Harness::new(2, 50, 10, 0.0);
+    let mover = harness.peers[0].net.id().unwrap();
+    let mut seen = Vec::new();
+
+    harness.run(
+        Duration::from_millis(1000),
+        |i| if i == 0 { forward(i) } else { idle(i) },
+        |harness| seen.extend(harness.peers[1].remote_pos(mover))
+    );
+
+    //interpolated positions keep moving forward between snapshots instead of jumping
+    let steps: Vec<f32> = seen
+        .windows(2)
+        .map(|pair| pair[1].x - pair[0].x)
+        .collect();
+    assert!(steps.iter().all(|step| *step >= -0.01), "remote player moved backwards");
+    let moving = steps.iter().filter(|step| **step > 0.0).count();
+    let snapshots = (1.0 / TICK_DELTA) as usize;
+    assert!(moving > snapshots, "remote player only moved on snapshots");
+}
@@ -0,0 +1,54 @@
+use indifi::*;
+use notan::math::Vec2;
+use notan::prelude::Color;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("indifi-profiles-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn text_round_trip() {
+    let mut profile = Profile::new("Ada Lovelace");
+    profile.color = Color::new(1.0, 0.5019608, 0.0, 1.0);
+    profile.seed = Some(42);
+    profile.position = Some(Vec2::new(2000.5, 1990.0));
+    profile.facing = Facing::Free;
+    profile.zoom = Some(10);
+    assert_eq!(Profile::parse(&profile.to_text()), Ok(profile));
+}
+
+#[test]
+fn rejects_bad_values() {
+    assert!(Profile::parse("color = #123456").is_err());
+    assert!(Profile::parse("name = a\ncolor = red").is_err());
+    assert!(Profile::parse("name = a\nzoom = -1").is_err());
+    assert!(Profile::parse("name = a\nposition = 1").is_err());
+    assert!(Profile::parse("name = a\nspeed = 3").is_err());
+}
+
+#[test]
+fn file_names_are_safe() {
+    assert_eq!(Profile::file_name("../x y"), "___x_y.profile");
+}
+
+#[test]
+fn chooses_by_name_or_last_played() {
+    let dir = temp_dir("choose");
+    let made = Profile::choose(&dir, Some("first")).unwrap();
+    assert_eq!(made.name, "first");
+    assert_eq!(Profile::choose(&dir, None).unwrap().name, "Blank");
+
+    made.save(&dir).unwrap();
+    //the last played profile is told apart by its modification time
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    let mut second = Profile::new("second");
+    second.position = Some(Vec2::new(10.0, 20.0));
+    second.save(&dir).unwrap();
+    assert_eq!(Profile::list(&dir).unwrap(), vec!["first".to_string(), "second".to_string()]);
+    assert_eq!(Profile::choose(&dir, Some("first")).unwrap().color, made.color);
+    assert_eq!(Profile::choose(&dir, None).unwrap(), second);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,167 @@
+use indifi::*;
+use notan::math::Vec2;
+
+const SEED: u64 = 1234;
+
+//the same crowd as the game, close enough to bump into the player
+fn build(simulation: SimulationBuilder) -> Simulation {
+    simulation
+        .npc(NpcBuilder::new(Behaviour::wander(Vec2::new(2000.0, 2000.0))).position(2000.0, 2000.0))
+        .npc(NpcBuilder::new(Behaviour::Follow { distance: 24.0 }).position(2100.0, 2048.0))
+        .npc(NpcBuilder::new(Behaviour::Flee { distance: 64.0 }).position(2048.0, 2120.0))
+        .build()
+}
+
+fn player_state(sim: &Simulation) -> (Vec2, f32) {
+    let transform = sim.world.transforms.get(sim.player()).unwrap();
+    (transform.position().vec(), transform.rotation().degrees())
+}
+
+fn record_session() -> (Replay, (Vec2, f32)) {
+    record(build(SimulationBuilder::new(SEED)))
+}
+
+//plays a scripted session with uneven frame times and records it like the game does
+fn record(mut sim: Simulation) -> (Replay, (Vec2, f32)) {
+    let mut replay = Replay::new(&sim);
+    for frame in 0..600 {
+        let center = sim.player_pos();
+        //the aim circles around the player so the rotation wraps past 0 and 360 a few times
+        let angle = (frame as f32) * 0.05;
+        let input = Input {
+            movement: match frame / 100 {
+                0 | 3 => Vec2::new(0.0, -1.0),
+                1 => Vec2::new(0.75, 0.5),
+                2 => Vec2::new(0.0, 0.0),
+                _ => Vec2::new(-0.75, -1.0),
+            },
+            aim: Some(center + Vec2::from_angle(angle) * 50.0),
+            move_to: None,
+        };
+        sim.apply_input(&input);
+        let frame_delta = if frame % 7 == 0 { 0.04 } else { 0.011 };
+        for _ in 0..sim.advance(frame_delta) {
+            replay.record(&sim);
+            sim.tick();
+        }
+    }
+    (replay, player_state(&sim))
+}
+
+#[test]
+fn playback_matches_the_recorded_session() {
+    let (replay, recorded) = record_session();
+    assert!(replay.ticks.len() > 100);
+
+    let mut sim = build(replay.simulation());
+    replay.play(&mut sim);
+    assert_eq!(player_state(&sim), recorded);
+}
+
+#[test]
+fn playback_starts_in_the_world_and_at_the_position_the_recording_did() {
+    let mut sim = build(SimulationBuilder::new(SEED));
+    let start = Vec2::new(1000.0, 1500.0);
+    sim.teleport_player(start);
+    //open ground around the start with a wall across the way the session walks first
+    for y in 78..108 {
+        for x in 47..77 {
+            sim.terrain.set_tile((x, y), if y == 88 { 17 } else { 0 }).unwrap();
+        }
+    }
+    let (replay, recorded) = record(sim);
+    assert_eq!(replay.start, start);
+    assert!(replay.terrain.is_some());
+
+    let loaded = Replay::decode(&replay.encode()).unwrap();
+    assert_eq!(loaded.start, start);
+    let mut sim = build(loaded.simulation());
+    assert_eq!(sim.player_pos(), start);
+    loaded.play(&mut sim);
+    assert_eq!(player_state(&sim), recorded);
+
+    //in the generated world the same inputs end up somewhere else
+    let mut unchanged = build(SimulationBuilder::new(SEED).player(PlayerBuilder::new().position(start.x, start.y)));
+    loaded.play(&mut unchanged);
+    assert_ne!(player_state(&unchanged), recorded);
+}
+
+#[test]
+fn a_generated_world_is_not_stored() {
+    let (replay, _) = record_session();
+    assert!(replay.terrain.is_none());
+    assert!(Replay::decode(&replay.encode()).unwrap().terrain.is_none());
+}
+
+#[test]
+fn playback_is_deterministic() {
+    let (replay, _) = record_session();
+    let mut a = build(replay.simulation());
+    let mut b = build(replay.simulation());
+    replay.play(&mut a);
+    replay.play(&mut b);
+    assert_eq!(player_state(&a), player_state(&b));
+}
+
+#[test]
+fn survives_a_round_trip_through_a_file() {
+    let (replay, recorded) = record_session();
+    let path = std::env::temp_dir().join(format!("indifi-replay-{}.bin", std::process::id()));
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.seed, SEED);
+    assert_eq!(loaded.area_size, AREA_SIZE);
+    assert_eq!(loaded.tick_rate, replay.tick_rate);
+    assert_eq!(loaded.ticks, replay.ticks);
+    let mut sim = build(loaded.simulation());
+    loaded.play(&mut sim);
+    assert_eq!(player_state(&sim), recorded);
+}
+
+#[test]
+fn rejects_files_that_are_not_replays() {
+    assert!(Replay::decode(b"").is_none());
+    assert!(Replay::decode(b"not a replay at all").is_none());
+    let (replay, _) = record_session();
+    let bytes = replay.encode();
+    assert!(Replay::decode(&bytes[..bytes.len() - 1]).is_none());
+}
+
+#[test]
+fn rejects_impossible_headers() {
+    let header = |area_size, tick_rate| {
+        Replay { seed: SEED, area_size, tick_rate, start: Vec2::new(8.0, 8.0), terrain: None, ticks: Vec::new() }.encode()
+    };
+    assert!(Replay::decode(&header(AREA_SIZE, 30.0)).is_some());
+    for area_size in [0, MAX_AREA_SIZE + 1, u16::MAX as usize] {
+        assert!(Replay::decode(&header(area_size, 30.0)).is_none(), "area size {}", area_size);
+    }
+    for tick_rate in [0.0, -30.0, f32::NAN, f32::INFINITY] {
+        let bytes = header(AREA_SIZE, tick_rate);
+        assert!(Replay::decode(&bytes).is_none(), "tick rate {}", tick_rate);
+    }
+    let start = Replay { seed: SEED, area_size: 1, tick_rate: 30.0, start: Vec2::NAN, terrain: None, ticks: Vec::new() };
+    assert!(Replay::decode(&start.encode()).is_none());
+    //a world that does not belong to the seed and size in the header
+    let terrain = Some(Terrain::generate_sized(SEED + 1, 1));
+    let other = Replay { seed: SEED, area_size: 1, tick_rate: 30.0, start: Vec2::ZERO, terrain, ticks: Vec::new() };
+    assert!(Replay::decode(&other.encode()).is_none());
+}
+
+#[test]
+fn turning_across_zero_takes_the_short_way() {
+    let mut sim = SimulationBuilder::new(SEED).build();
+    let mut replay = Replay::new(&sim);
+    //face 350 degrees, then 10, which is 20 degrees away and not 340
+    replay.ticks.extend(
+        (0..120).map(|_| TickInput { movement: Vec2::new(0.0, 0.0), desired_rotation: Angle::from_degrees(350.0) })
+    );
+    replay.ticks.extend(
+        (0..12).map(|_| TickInput { movement: Vec2::new(0.0, 0.0), desired_rotation: Angle::from_degrees(10.0) })
+    );
+    replay.play(&mut sim);
+    let rotation = sim.world.transforms.get(sim.player()).unwrap().rotation();
+    assert!(rotation.difference(Angle::from_degrees(10.0)).abs() < 1.0, "ended at {}", rotation.degrees());
+}
@@ -0,0 +1,75 @@
+use indifi::*;
+
+struct Named {
+    name: &'static str,
+    overlay: bool,
+}
+
+fn scene(name: &'static str) -> Named {
+    Named { name, overlay: false }
+}
+
+fn overlay(name: &'static str) -> Named {
+    Named { name, overlay: true }
+}
+
+impl Scene for Named {
+    type Context = Vec<String>;
+
+    fn enter(&mut self, log: &mut Vec<String>) {
+        log.push(format!("enter {}", self.name));
+    }
+
+    fn exit(&mut self, log: &mut Vec<String>) {
+        log.push(format!("exit {}", self.name));
+    }
+
+    fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+}
+
+fn names(stack: &mut SceneStack<Named>) -> Vec<&'static str> {
+    stack.visible_mut().iter().map(|scene| scene.name).collect()
+}
+
+#[test]
+fn covering_a_scene_does_not_exit_it() {
+    let mut log = Vec::new();
+    let mut stack = SceneStack::new();
+    stack.push(scene("menu"), &mut log);
+    stack.apply(Transition::Replace(scene("game")), &mut log);
+    stack.apply(Transition::Push(overlay("pause")), &mut log);
+    stack.apply(Transition::Pop, &mut log);
+    assert_eq!(log, ["enter menu", "exit menu", "enter game", "enter pause", "exit pause"]);
+    assert_eq!(stack.top().map(|scene| scene.name), Some("game"));
+}
+
+#[test]
+fn reset_exits_from_the_top() {
+    let mut log = Vec::new();
+    let mut stack = SceneStack::new();
+    stack.push(scene("game"), &mut log);
+    stack.push(overlay("pause"), &mut log);
+    log.clear();
+    stack.apply(Transition::Reset(scene("menu")), &mut log);
+    assert_eq!(log, ["exit pause", "exit game", "enter menu"]);
+    assert_eq!(stack.len(), 1);
+    stack.clear(&mut log);
+    assert!(stack.is_empty());
+    stack.apply(Transition::Pop, &mut log);
+}
+
+#[test]
+fn overlays_show_what_is_below() {
+    let mut log = Vec::new();
+    let mut stack = SceneStack::new();
+    stack.push(scene("menu"), &mut log);
+    stack.push(scene("game"), &mut log);
+    assert_eq!(names(&mut stack), ["game"]);
+    stack.push(overlay("pause"), &mut log);
+    stack.push(overlay("bindings"), &mut log);
+    assert_eq!(names(&mut stack), ["game", "pause", "bindings"]);
+    stack.push(scene("editor"), &mut log);
+    assert_eq!(names(&mut stack), ["editor"]);
+}
@@ -0,0 +1,182 @@
+use indifi::net::*;
+use indifi::server::{ Server, ServerEvent, MAX_CLIENTS };
+use indifi::*;
+use notan::math::Vec2;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+//a server and a raw socket that speaks its protocol, without a NetClient in between
+struct Connection {
+    server: Server,
+    socket: UdpSocket,
+}
+
+impl Connection {
+    fn new() -> Self {
+        let server = Server::bind("127.0.0.1:0", 5).unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        socket.connect(server.local_addr().unwrap()).unwrap();
+        let mut connection = Connection { server, socket };
+        connection.send(&(ClientMessage::Hello { display_name: "cheater".to_string(), color: [1.0, 0.0, 0.0] }));
+        assert!(matches!(connection.next_message(), ServerMessage::Welcome { .. }));
+        connection
+    }
+
+    fn send(&mut self, message: &ClientMessage) {
+        self.socket.send(&message.encode()).unwrap();
+        //loopback delivery is not instant
+        std::thread::sleep(Duration::from_millis(20));
+        self.server.receive().unwrap();
+    }
+
+    fn next_message(&self) -> ServerMessage {
+        let mut buffer = vec![0; 65536];
+        let len = self.socket.recv(&mut buffer).unwrap();
+        ServerMessage::decode(&buffer[..len]).unwrap()
+    }
+
+    //steps the server and returns what it says about the player
+    fn step(&mut self) -> PlayerSnapshot {
+        self.server.step();
+        self.server.broadcast();
+        loop {
+            if let ServerMessage::Snapshot { mut players, .. } = self.next_message() {
+                return players.remove(0);
+            }
+        }
+    }
+}
+
+fn forward(seqs: impl Iterator<Item = u32>) -> ClientMessage {
+    let commands = seqs
+        .map(|seq| InputCommand { seq, movement: Vec2::new(0.0, -1.0), desired_rotation: Angle::ZERO })
+        .collect();
+    ClientMessage::Input { commands }
+}
+
+fn position(snapshot: &PlayerSnapshot) -> Vec2 {
+    Vec2::new(snapshot.x, snapshot.y)
+}
+
+#[test]
+fn a_packet_full_of_inputs_does_not_move_faster() {
+    let mut connection = Connection::new();
+    let start = position(&connection.step());
+    connection.send(&forward(1..=255));
+    let snapshot = connection.step();
+    //the oldest inputs were dropped and acknowledged, the tick before saved up one more to apply
+    assert_eq!(snapshot.last_input, 255 - 32 + 2);
+    let speed = connection.server.world().transforms.iter().next().unwrap().1.movement_speed();
+    assert!(position(&snapshot).distance(start) <= speed * TICK_DELTA * 2.0);
+
+    //the rest comes one a tick, plus what was saved up while nothing was waiting
+    let mut last = snapshot.last_input;
+    for _ in 0..5 {
+        let snapshot = connection.step();
+        assert_eq!(snapshot.last_input, last + 1);
+        last = snapshot.last_input;
+    }
+}
+
+#[test]
+fn late_inputs_catch_up_a_little() {
+    let mut connection = Connection::new();
+    for _ in 0..5 {
+        connection.step();
+    }
+    connection.send(&forward(1..=5));
+    assert_eq!(connection.step().last_input, 3);
+    assert_eq!(connection.step().last_input, 4);
+}
+
+#[test]
+fn inputs_far_ahead_are_ignored() {
+    let mut connection = Connection::new();
+    let start = position(&connection.step());
+    connection.send(&forward([10_000].into_iter()));
+    let snapshot = connection.step();
+    assert_eq!(snapshot.last_input, 0);
+    assert_eq!(position(&snapshot), start);
+}
+
+#[test]
+fn non_finite_inputs_are_dropped() {
+    let mut connection = Connection::new();
+    let start = position(&connection.step());
+    let commands = vec![
+        InputCommand { seq: 1, movement: Vec2::new(f32::NAN, 0.0), desired_rotation: Angle::ZERO },
+        InputCommand { seq: 2, movement: Vec2::new(0.0, f32::INFINITY), desired_rotation: Angle::ZERO }
+    ];
+    connection.send(&(ClientMessage::Input { commands }));
+    for _ in 0..3 {
+        let snapshot = connection.step();
+        assert_eq!(snapshot.last_input, 0);
+        assert!(snapshot.x.is_finite() && snapshot.y.is_finite());
+        assert_eq!(position(&snapshot), start);
+    }
+    //an honest input after them still goes through
+    connection.send(&forward(1..=1));
+    assert_eq!(connection.step().last_input, 1);
+}
+
+#[test]
+fn a_full_server_refuses_new_players() {
+    let mut server = Server::bind("127.0.0.1:0", 5).unwrap();
+    let hello = (ClientMessage::Hello { display_name: "player".to_string(), color: [1.0, 0.0, 0.0] }).encode();
+    let sockets: Vec<UdpSocket> = (0..MAX_CLIENTS + 1)
+        .map(|_| {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+            socket.connect(server.local_addr().unwrap()).unwrap();
+            socket
+        })
+        .collect();
+    for socket in &sockets {
+        socket.send(&hello).unwrap();
+        //one at a time so the last one is the one turned away
+        std::thread::sleep(Duration::from_millis(2));
+        server.receive().unwrap();
+    }
+    assert_eq!(server.client_count(), MAX_CLIENTS);
+    let refused = sockets.last().unwrap();
+    let events = server.take_events();
+    let joined = events.iter().filter(|event| matches!(event, ServerEvent::Joined { .. })).count();
+    assert_eq!(joined, MAX_CLIENTS);
+    assert!(matches!(events.last(), Some(ServerEvent::Refused { addr }) if *addr == refused.local_addr().unwrap()));
+
+    let mut buffer = vec![0; 65536];
+    assert!(refused.recv(&mut buffer).is_err());
+    let len = sockets[0].recv(&mut buffer).unwrap();
+    assert!(matches!(ServerMessage::decode(&buffer[..len]), Some(ServerMessage::Welcome { .. })));
+}
+
+#[test]
+fn joins_and_leaves_are_events_and_the_world_size_is_sent() {
+    let mut server = Server::bind_sized("127.0.0.1:0", 5, 3).unwrap();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+    socket.connect(server.local_addr().unwrap()).unwrap();
+    let send = |server: &mut Server, message: ClientMessage| {
+        socket.send(&message.encode()).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        server.receive().unwrap();
+    };
+
+    send(&mut server, ClientMessage::Hello { display_name: "someone".to_string(), color: [1.0, 0.0, 0.0] });
+    let mut buffer = vec![0; 65536];
+    let len = socket.recv(&mut buffer).unwrap();
+    let Some(ServerMessage::Welcome { id, area_size, .. }) = ServerMessage::decode(&buffer[..len]) else {
+        panic!("not a welcome");
+    };
+    assert_eq!(area_size, 3);
+    let events = server.take_events();
+    assert!(
+        matches!(events.as_slice(), [ServerEvent::Joined { id: joined, display_name, addr }]
+            if *joined == id && display_name == "someone" && *addr == socket.local_addr().unwrap())
+    );
+
+    send(&mut server, ClientMessage::Bye);
+    assert!(matches!(server.take_events().as_slice(), [ServerEvent::Left { id: left }] if *left == id));
+    assert_eq!(server.client_count(), 0);
+}
@@ -0,0 +1,242 @@
+use indifi::*;
+use notan::math::Vec2;
+
+//floor without ice, mud or walls this many tiles around the player, so how far it gets only
+//depends on the movement and not on what the seed put there
+fn clear_ground(sim: &mut Simulation, radius: usize) {
+    let (x, y) = Chunk::pos_to_tile(sim.player_pos());
+    for ty in y - radius..=y + radius {
+        for tx in x - radius..=x + radius {
+            sim.terrain.set_tile((tx, ty), 0);
+        }
+    }
+}
+
+#[test]
+fn runs_without_a_window() {
+    let mut sim = SimulationBuilder::new(1).build();
+    clear_ground(&mut sim, 6);
+    let start = sim.player_pos();
+    let input = Input { movement: Vec2::new(0.0, -1.0), ..Default::default() };
+
+    //one second at the default tick rate
+    let ticks: u32 = (0..60).map(|_| sim.update(&input, 1.0 / 60.0)).sum();
+    assert!(ticks >= 59);
+    assert!(sim.player_pos().distance(start) > 40.0);
+}
+
+#[test]
+fn aim_turns_the_player() {
+    let mut sim = SimulationBuilder::new(1).build();
+    let target = sim.player_pos() + Vec2::new(0.0, 100.0);
+    let input = Input { aim: Some(target), ..Default::default() };
+    for _ in 0..120 {
+        sim.update(&input, 1.0 / 60.0);
+    }
+    let rotation = sim.world.transforms.get(sim.player()).unwrap().rotation();
+    assert!(rotation.difference(Angle::from_degrees(90.0)).abs() < 1.0);
+}
+
+#[test]
+fn same_seed_same_terrain() {
+    let a = Terrain::generate(42);
+    let b = Terrain::generate(42);
+    let c = Terrain::generate(43);
+    let size = a.size_in_tiles();
+    let walls = |terrain: &Terrain| -> Vec<bool> {
+        (0..size * size).map(|i| terrain.is_wall((i % size, i / size))).collect()
+    };
+    assert_eq!(walls(&a), walls(&b));
+    assert_ne!(walls(&a), walls(&c));
+}
+
+#[test]
+fn move_to_walks_around_walls() {
+    let mut sim = SimulationBuilder::new(7).build();
+    clear_ground(&mut sim, 6);
+    //a wall five tiles high right between the player and the goal
+    let (x, y) = Chunk::pos_to_tile(sim.player_pos());
+    for ty in y - 2..=y + 2 {
+        sim.terrain.set_wall((x + 2, ty), true);
+    }
+    let start = sim.player_pos();
+    let goal = Chunk::tile_center((x + 4, y));
+    let path = sim.terrain.find_path(start, goal).expect("the wall can be walked around");
+    assert!(path.iter().any(|point| Chunk::pos_to_tile(*point).1 != y));
+
+    sim.update(&(Input { move_to: Some(goal), ..Default::default() }), 0.0);
+    for _ in 0..60 * 20 {
+        sim.update(&Input::default(), 1.0 / 60.0);
+    }
+    assert!(sim.player_pos().distance(goal) < 16.0);
+}
+
+#[test]
+fn screen_relative_walks_up_the_screen_whatever_the_facing() {
+    let mut sim = SimulationBuilder::new(1)
+        .player(PlayerBuilder::new().movement_model(MovementModel::ScreenRelative))
+        .build();
+    clear_ground(&mut sim, 6);
+    let start = sim.player_pos();
+    //faces right while walking up
+    let input = Input {
+        movement: Vec2::new(0.0, -1.0),
+        aim: Some(start + Vec2::new(1000.0, 0.0)),
+        move_to: None,
+    };
+    for _ in 0..60 {
+        sim.update(&input, 1.0 / 60.0);
+    }
+    let moved = sim.player_pos() - start;
+    assert!(moved.y < -20.0);
+    assert!(moved.x.abs() < 1.0);
+}
+
+#[test]
+fn speeds_up_and_stops_at_the_acceleration_and_friction() {
+    let forward = Vec2::new(0.0, -1.0);
+    let still = Vec2::new(0.0, 0.0);
+    //50 units a second reached at 600 units a second squared takes 5 ticks at 60 a second
+    let mut transform = TransformPR::new();
+    for tick in 1..=5 {
+        transform.update(1.0 / 60.0, forward, Angle::ZERO, Surface::default());
+        assert!((transform.speed() - (tick as f32) * 10.0).abs() < 0.01);
+    }
+    transform.update(1.0 / 60.0, forward, Angle::ZERO, Surface::default());
+    assert!((transform.speed() - 50.0).abs() < 0.01);
+
+    //and braking at 600 stops it in 5 more ticks, 40 + 30 + 20 + 10 + 0 per second of them
+    let start = transform.position().vec();
+    for _ in 0..5 {
+        transform.update(1.0 / 60.0, still, Angle::ZERO, Surface::default());
+    }
+    assert_eq!(transform.speed(), 0.0);
+    assert!((transform.position().vec().distance(start) - 100.0 / 60.0).abs() < 0.01);
+}
+
+#[test]
+fn ice_slides_and_mud_slows() {
+    let forward = Vec2::new(0.0, -1.0);
+    let still = Vec2::new(0.0, 0.0);
+    let ice = Surface { grip: 0.15, speed: 1.0 };
+    let mud = Surface { grip: 2.0, speed: 0.5 };
+    let stopping_distance = |surface: Surface| {
+        let mut transform = TransformPR::new();
+        for _ in 0..120 {
+            transform.update(1.0 / 60.0, forward, Angle::ZERO, surface);
+        }
+        let start = transform.position().vec();
+        for _ in 0..600 {
+            transform.update(1.0 / 60.0, still, Angle::ZERO, surface);
+        }
+        transform.position().vec().distance(start)
+    };
+    assert!(stopping_distance(ice) > stopping_distance(Surface::default()) * 4.0);
+
+    let mut transform = TransformPR::new();
+    for _ in 0..120 {
+        transform.update(1.0 / 60.0, forward, Angle::ZERO, mud);
+    }
+    assert!((transform.speed() - 25.0).abs() < 0.01);
+}
+
+#[test]
+fn knockback_fades_through_friction() {
+    let mut transform = TransformPR::new();
+    transform.knockback(Vec2::new(200.0, 0.0));
+    assert_eq!(transform.speed(), 200.0);
+    let start = transform.position().vec();
+    for _ in 0..60 {
+        transform.update(1.0 / 60.0, Vec2::new(0.0, 0.0), Angle::ZERO, Surface::default());
+    }
+    assert_eq!(transform.speed(), 0.0);
+    assert!(transform.position().x() > start.x + 20.0);
+}
+
+#[test]
+fn world_files_keep_every_tile() {
+    let terrain = Terrain::generate_sized(11, 3);
+    let loaded = Terrain::decode(&terrain.encode()).unwrap();
+    assert_eq!((loaded.seed(), loaded.area_size()), (11, 3));
+    let size = terrain.size_in_tiles();
+    for y in 0..size {
+        for x in 0..size {
+            assert_eq!(terrain.is_wall((x, y)), loaded.is_wall((x, y)));
+        }
+    }
+    assert_eq!(terrain.stats(), loaded.stats());
+    let stats = terrain.stats();
+    assert_eq!(stats.walls + stats.floor + stats.ice + stats.mud, stats.tiles);
+    assert!(Terrain::decode(&terrain.encode()[..100]).is_none());
+}
+
+#[test]
+fn generating_a_chunk_at_a_time_makes_the_same_world() {
+    let mut generator = TerrainGenerator::new(5, 3);
+    assert_eq!(generator.progress(), 0.0);
+    let mut steps = 0;
+    while generator.step() {
+        steps += 1;
+    }
+    assert_eq!(steps, 9);
+    assert!(generator.is_done());
+    assert_eq!(generator.progress(), 1.0);
+    let stepped = generator.finish();
+    let whole = Terrain::generate_sized(5, 3);
+    assert_eq!(stepped.encode(), whole.encode());
+
+    let builder = SimulationBuilder::new(5).area_size(3);
+    assert_eq!(builder.terrain_generator().unwrap().finish().encode(), whole.encode());
+    assert!(builder.terrain(whole).terrain_generator().is_none());
+}
+
+#[test]
+fn walls_can_be_built_and_taken_away() {
+    let mut terrain = Terrain::generate_sized(5, 2);
+    let size = terrain.size_in_tiles();
+    let floor = (0..size * size)
+        .map(|i| (i % size, i / size))
+        .find(|tile| !terrain.is_wall(*tile))
+        .unwrap();
+    assert_eq!(terrain.set_wall(floor, true), Some((floor.0 / CHUNK_TILES, floor.1 / CHUNK_TILES)));
+    assert!(terrain.is_wall(floor));
+    //the built wall is what gets drawn there
+    assert_eq!(terrain.visible_tile(floor), Some(17));
+    assert_eq!(terrain.visible_tile((size, 0)), None);
+    assert_eq!(terrain.set_wall(floor, true), None);
+    assert!(terrain.set_wall(floor, false).is_some());
+    assert!(!terrain.is_wall(floor));
+    assert_eq!(terrain.set_wall((size, 0), true), None);
+}
+
+#[test]
+fn tiles_can_be_set_and_chunks_regenerated() {
+    let mut terrain = Terrain::generate_sized(5, 2);
+    let original = terrain.encode();
+    let tile = (CHUNK_TILES + 3, 4);
+    assert_eq!(terrain.set_tile(tile, 20), Some((1, 0)));
+    assert!(terrain.is_wall(tile));
+    terrain.set_tile(tile, 2);
+    assert!(!terrain.is_wall(tile));
+    assert_eq!(terrain.visible_tile(tile), Some(2));
+    assert_eq!(terrain.set_tile((0, 2 * CHUNK_TILES), 2), None);
+
+    assert!(terrain.regenerate_chunk((1, 0)));
+    assert!(!terrain.regenerate_chunk((2, 0)));
+    assert_eq!(terrain.encode(), original);
+}
+
+#[test]
+fn chunk_changes_are_judged_by_the_length_of_a_tick() {
+    let mut sim = SimulationBuilder::new(1).build();
+    let player = sim.player();
+    //players start out counted in chunk 1 1
+    sim.teleport_player(Vec2::new(600.0, 300.0));
+    sim.world.transforms.get_mut(player).unwrap().set_velocity(Vec2::new(100.0, 0.0));
+    let transform = sim.world.transforms.get(player).unwrap();
+    let state = sim.world.players.get_mut(player).unwrap();
+    //100 units in a one second tick is more than a quarter chunk, in a sixtieth of one it is not
+    assert!(!state.check_chunk_change(transform, 1.0));
+    assert!(state.check_chunk_change(transform, 1.0 / 60.0));
+    assert_eq!(state.last_coords(), (2, 1));
+}
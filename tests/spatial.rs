@@ -0,0 +1,82 @@
+use indifi::*;
+use notan::math::Vec2;
+
+//one cell per chunk
+const CELL: f32 = 256.0;
+
+#[test]
+fn entities_on_a_border_belong_to_the_cell_after_it() {
+    assert_eq!(Chunk::size() as f32, CELL);
+    let mut index = SpatialIndex::new();
+    index.update(1, Vec2::new(CELL - 0.01, 10.0));
+    index.update(2, Vec2::new(CELL, 10.0));
+    index.update(3, Vec2::new(CELL, CELL));
+    assert_eq!(index.cell((0, 0)), &[1]);
+    assert_eq!(index.cell((1, 0)), &[2]);
+    assert_eq!(index.cell((1, 1)), &[3]);
+}
+
+#[test]
+fn queries_reach_across_borders_and_include_their_edges() {
+    let mut index = SpatialIndex::new();
+    index.update(1, Vec2::new(CELL, 10.0));
+    index.update(2, Vec2::new(CELL - 20.0, 10.0));
+    //the center is in the cell before the entity, the entity is exactly on the circle
+    let mut found = index.query_radius(Vec2::new(CELL - 6.0, 10.0), 6.0);
+    found.sort();
+    assert_eq!(found, vec![1]);
+    assert_eq!(index.query_radius(Vec2::new(CELL - 6.0, 10.0), 5.9), Vec::<Entity>::new());
+
+    let mut found = index.query_rect(Vec2::new(CELL - 20.0, 10.0), Vec2::new(CELL, 10.0));
+    found.sort();
+    assert_eq!(found, vec![1, 2]);
+    assert_eq!(index.query_rect(Vec2::new(CELL - 19.0, 0.0), Vec2::new(CELL - 0.5, 20.0)), Vec::<Entity>::new());
+}
+
+#[test]
+fn moving_entities_change_cells() {
+    let mut index = SpatialIndex::new();
+    index.update(7, Vec2::new(10.0, 10.0));
+    index.update(7, Vec2::new(100.0, 200.0));
+    assert_eq!(index.cell((0, 0)), &[7]);
+    assert_eq!(index.position(7), Some(Vec2::new(100.0, 200.0)));
+
+    index.update(7, Vec2::new(CELL + 1.0, 200.0));
+    assert!(index.cell((0, 0)).is_empty());
+    assert_eq!(index.cell((1, 0)), &[7]);
+    assert_eq!(index.query_radius(Vec2::new(100.0, 200.0), 50.0), Vec::<Entity>::new());
+    assert_eq!(index.query_radius(Vec2::new(CELL + 1.0, 200.0), 1.0), vec![7]);
+
+    //and back, without being listed twice anywhere
+    index.update(7, Vec2::new(10.0, 10.0));
+    assert_eq!(index.cell((0, 0)), &[7]);
+    assert!(index.cell((1, 0)).is_empty());
+    assert_eq!(index.query_coords((0, 0), (3, 3)).count(), 1);
+}
+
+#[test]
+fn despawned_entities_are_gone_from_the_index() {
+    let mut world = World::new();
+    let npc = NpcBuilder::new(Behaviour::wander(Vec2::new(300.0, 300.0)))
+        .position(300.0, 300.0)
+        .spawn(&mut world);
+    let other = NpcBuilder::new(Behaviour::wander(Vec2::new(310.0, 300.0)))
+        .position(310.0, 300.0)
+        .spawn(&mut world);
+    spatial_system(&mut world);
+    assert_eq!(world.spatial.cell((1, 1)).len(), 2);
+
+    world.despawn(npc);
+    assert_eq!(world.spatial.position(npc), None);
+    assert_eq!(world.spatial.cell((1, 1)), &[other]);
+    assert_eq!(world.spatial.query_radius(Vec2::new(300.0, 300.0), 5.0), Vec::<Entity>::new());
+
+    //the id comes back for the next entity, which is only where it was spawned
+    let reused = NpcBuilder::new(Behaviour::wander(Vec2::new(10.0, 10.0)))
+        .position(10.0, 10.0)
+        .spawn(&mut world);
+    assert_eq!(reused, npc);
+    spatial_system(&mut world);
+    assert_eq!(world.spatial.cell((1, 1)), &[other]);
+    assert_eq!(world.spatial.cell((0, 0)), &[reused]);
+}
@@ -0,0 +1,55 @@
+use indifi::*;
+
+#[test]
+fn hands_out_whole_ticks_and_keeps_the_rest() {
+    let mut timestep = FixedTimestep::new(10.0);
+    assert_eq!(timestep.advance(0.05), 0);
+    assert!((timestep.alpha() - 0.5).abs() < 0.001);
+    assert_eq!(timestep.advance(0.06), 1);
+    assert!((timestep.alpha() - 0.1).abs() < 0.001);
+    assert_eq!(timestep.advance(0.24), 2);
+    assert!((timestep.alpha() - 0.5).abs() < 0.001);
+}
+
+#[test]
+fn a_long_hitch_skips_ahead() {
+    let mut timestep = FixedTimestep::new(60.0);
+    assert_eq!(timestep.advance(10.0), 8);
+    assert_eq!(timestep.alpha(), 0.0);
+    assert_eq!(timestep.advance(1.0 / 60.0 + 0.0001), 1);
+}
+
+#[test]
+fn alpha_stays_between_0_and_1() {
+    let mut timestep = FixedTimestep::new(30.0);
+    assert_eq!(timestep.alpha(), 0.0);
+    for _ in 0..100 {
+        timestep.advance(0.007);
+        assert!((0.0..1.0).contains(&timestep.alpha()));
+    }
+}
+
+#[test]
+fn bad_frame_times_add_nothing() {
+    let mut timestep = FixedTimestep::new(60.0);
+    for frame_delta in [f32::NAN, f32::INFINITY, -1.0] {
+        assert_eq!(timestep.advance(frame_delta), 0);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+    assert_eq!(timestep.advance(1.0 / 60.0 + 0.0001), 1);
+}
+
+#[test]
+fn tick_rates_are_kept_in_range() {
+    let close = |a: f32, b: f32| (a - b).abs() < 0.01;
+    assert!(close(FixedTimestep::new(0.0).tick_rate(), MIN_TICK_RATE));
+    assert!(close(FixedTimestep::new(-5.0).tick_rate(), MIN_TICK_RATE));
+    assert!(close(FixedTimestep::new(1e9).tick_rate(), MAX_TICK_RATE));
+    assert!(close(FixedTimestep::new(f32::NAN).tick_rate(), DEFAULT_TICK_RATE));
+    let mut timestep = FixedTimestep::new(60.0);
+    timestep.set_tick_rate(f32::INFINITY);
+    assert!(close(timestep.tick_rate(), DEFAULT_TICK_RATE));
+    timestep.set_tick_rate(20.0);
+    assert!((timestep.delta() - 0.05).abs() < 1e-6);
+    assert_eq!(timestep.advance(0.12), 2);
+}
@@ -0,0 +1,93 @@
+use indifi::*;
+use notan::math::{ Rect, Vec2 };
+use notan::prelude::KeyCode;
+
+const BUTTON: Rect = Rect { x: 10.0, y: 10.0, width: 100.0, height: 20.0 };
+const SLIDER: Rect = Rect { x: 10.0, y: 40.0, width: 100.0, height: 20.0 };
+
+fn click(x: f32, y: f32) -> UiInput {
+    UiInput { mouse: Vec2::new(x, y), pressed: true, down: true, ..UiInput::none() }
+}
+
+fn keys(keys: &[KeyCode]) -> UiInput {
+    UiInput { keys: keys.to_vec(), ..UiInput::none() }
+}
+
+fn typed(text: &str) -> UiInput {
+    UiInput { text: text.to_string(), ..UiInput::none() }
+}
+
+//one frame with a button and a slider, what the button did
+fn frame(state: &mut UiState, input: &UiInput, value: &mut f32) -> bool {
+    state.begin(input);
+    let (clicked, _) = state.button(input, BUTTON);
+    state.slider(input, SLIDER, value, (0.0, 10.0), 1.0);
+    state.end(input);
+    clicked
+}
+
+#[test]
+fn buttons_and_checkboxes_take_clicks() {
+    let mut state = UiState::default();
+    let mut value = 0.0;
+    assert!(frame(&mut state, &click(50.0, 15.0), &mut value));
+    assert!(!frame(&mut state, &click(50.0, 35.0), &mut value));
+    assert!(state.wants_mouse(Vec2::new(50.0, 15.0)));
+    assert!(!state.wants_mouse(Vec2::new(200.0, 15.0)));
+
+    let mut checked = false;
+    let input = click(15.0, 15.0);
+    state.begin(&input);
+    assert!(state.checkbox(&input, BUTTON, &mut checked).0);
+    state.end(&input);
+    assert!(checked);
+}
+
+#[test]
+fn sliders_follow_the_mouse_until_let_go() {
+    let mut state = UiState::default();
+    let mut value = 0.0;
+    frame(&mut state, &click(45.0, 50.0), &mut value);
+    assert_eq!(value, 4.0);
+    //still held when dragged off the slider and over the button
+    let drag = UiInput { pressed: false, ..click(200.0, 15.0) };
+    assert!(!frame(&mut state, &drag, &mut value));
+    assert_eq!(value, 10.0);
+    frame(&mut state, &UiInput::none(), &mut value);
+    assert!(!state.wants_mouse(Vec2::new(200.0, 15.0)));
+}
+
+#[test]
+fn the_keyboard_moves_between_widgets() {
+    let mut state = UiState::default().with_keyboard();
+    let mut value = 5.0;
+    assert!(frame(&mut state, &keys(&[KeyCode::Return]), &mut value));
+    frame(&mut state, &keys(&[KeyCode::Down]), &mut value);
+    frame(&mut state, &keys(&[KeyCode::Right]), &mut value);
+    assert_eq!(value, 6.0);
+    //wraps around to the button
+    assert!(frame(&mut state, &keys(&[KeyCode::Tab, KeyCode::Return]), &mut value));
+
+    let mut ignored = UiState::default();
+    assert!(!frame(&mut ignored, &keys(&[KeyCode::Return]), &mut value));
+}
+
+#[test]
+fn text_fields_type_while_editing() {
+    let mut state = UiState::default();
+    let mut text = String::from("ab");
+    let mut field = |state: &mut UiState, input: &UiInput| {
+        state.begin(input);
+        let (changed, _) = state.text_field(input, BUTTON, &mut text);
+        state.end(input);
+        changed
+    };
+    assert!(!field(&mut state, &typed("x")));
+    field(&mut state, &click(20.0, 15.0));
+    assert!(state.is_editing());
+    assert!(field(&mut state, &typed("cd\n")));
+    assert!(field(&mut state, &keys(&[KeyCode::Back])));
+    field(&mut state, &keys(&[KeyCode::Return]));
+    assert!(!state.is_editing());
+    assert_eq!(text, "abc");
+}